@@ -0,0 +1,31 @@
+//! Helpers shared by the benches. Each bench uses only some of them.
+#![allow(dead_code)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[path = "../../src/utils/rng.rs"]
+pub mod rng;
+
+/// Runs `f` once to warm up, then prints and returns the best of `runs` timings.
+pub fn bench<T>(name: &str, runs: usize, mut f: impl FnMut() -> T) -> Duration {
+    black_box(f());
+    let best = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    println!("{name:<24} {best:>12.3?}");
+    best
+}
+
+/// Prints how many times faster `new` is than `old`.
+pub fn speedup(old: Duration, new: Duration) {
+    println!(
+        "  speedup                {:>11.2}x",
+        old.as_secs_f64() / new.as_secs_f64()
+    );
+}
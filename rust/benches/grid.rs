@@ -0,0 +1,92 @@
+mod common;
+
+use aoc::grid::{BitGrid, Grid};
+use aoc::sweep::{Op, Sweep};
+use common::rng::Rng;
+use common::{bench, speedup};
+
+/// 300 random 2015 day 6 instructions: action (0 on, 1 off, 2 toggle) and corners.
+fn instructions() -> Vec<(u8, [usize; 4])> {
+    let mut rng = Rng::default();
+    let mut next = |n: u64| rng.below(n) as usize;
+    (0..300)
+        .map(|_| {
+            let (x1, y1) = (next(1000), next(1000));
+            let (x2, y2) = (x1 + next(1000 - x1 as u64), y1 + next(1000 - y1 as u64));
+            (next(3) as u8, [x1, y1, x2, y2])
+        })
+        .collect()
+}
+
+fn with_grid(input: &[(u8, [usize; 4])]) -> usize {
+    let mut grid = Grid::new(1000, 1000, false);
+    for &(action, [x1, y1, x2, y2]) in input {
+        let mut lights = grid.view_mut(x1..=x2, y1..=y2);
+        match action {
+            0 => lights.fill(true),
+            1 => lights.fill(false),
+            _ => lights.map_in_place(|b| !b),
+        }
+    }
+    grid.iter().filter(|x| **x).count()
+}
+
+fn with_bit_grid(input: &[(u8, [usize; 4])]) -> usize {
+    let mut grid = BitGrid::new(1000, 1000);
+    for &(action, [x1, y1, x2, y2]) in input {
+        match action {
+            0 => grid.set_rect(x1..=x2, y1..=y2),
+            1 => grid.clear_rect(x1..=x2, y1..=y2),
+            _ => grid.toggle_rect(x1..=x2, y1..=y2),
+        }
+    }
+    grid.count_ones()
+}
+
+fn brightness_with_grid(input: &[(u8, [usize; 4])]) -> usize {
+    let mut grid = Grid::new(1000, 1000, 0usize);
+    for &(action, [x1, y1, x2, y2]) in input {
+        let mut lights = grid.view_mut(x1..=x2, y1..=y2);
+        match action {
+            0 => lights.map_in_place(|i| i + 1),
+            1 => lights.map_in_place(|i| i.saturating_sub(1)),
+            _ => lights.map_in_place(|i| i + 2),
+        }
+    }
+    grid.iter().sum()
+}
+
+fn brightness_with_sweep(input: &[(u8, [usize; 4])]) -> usize {
+    let mut sweep = Sweep::new();
+    for &(action, [x1, y1, x2, y2]) in input {
+        let op = match action {
+            0 => Op::Add(1),
+            1 => Op::SaturatingSub(1),
+            _ => Op::Add(2),
+        };
+        sweep.push(op, x1 as i64..=x2 as i64, y1 as i64..=y2 as i64);
+    }
+    sweep.sum() as usize
+}
+
+fn main() {
+    let input = instructions();
+    assert_eq!(with_grid(&input), with_bit_grid(&input));
+
+    println!("2015 day 6 part 1");
+    let grid = bench("  Grid<bool>", 10, || with_grid(&input));
+    let bits = bench("  BitGrid", 10, || with_bit_grid(&input));
+    speedup(grid, bits);
+
+    assert_eq!(brightness_with_grid(&input), brightness_with_sweep(&input));
+    println!("2015 day 6 part 2");
+    let grid = bench("  Grid<usize>", 10, || brightness_with_grid(&input));
+    let sweep = bench("  Sweep", 10, || brightness_with_sweep(&input));
+    speedup(grid, sweep);
+
+    let mut life = BitGrid::new(100, 100);
+    life.set_rect(10..90, 10..90);
+    bench("life, 100 steps", 10, || {
+        (0..100).fold(life.clone(), |grid, _| grid.life_step(&[3], &[2, 3]))
+    });
+}
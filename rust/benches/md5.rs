@@ -0,0 +1,55 @@
+mod common;
+
+use aoc::md5::{Digest, Md5, digits};
+use common::{bench, speedup};
+
+fn six_zeros(digest: &Digest) -> bool {
+    digest.leading_zero_nibbles() >= 6
+}
+
+/// 2015 day 4 part 2, one candidate at a time.
+fn scalar_search(key: &str) -> u64 {
+    let mut prefix = Md5::new();
+    prefix.update(key);
+
+    let mut buf = [0u8; 20];
+    for i in 0.. {
+        let mut md5 = prefix.clone();
+        md5.update(digits(i, &mut buf));
+        if six_zeros(&md5.finalize()) {
+            return i;
+        }
+    }
+    unreachable!()
+}
+
+/// The same search, `N` candidates per batch.
+fn batch_search<const N: usize>(key: &str) -> u64 {
+    let mut prefix = Md5::new();
+    prefix.update(key);
+
+    let mut bufs = [[0u8; 20]; N];
+    for base in (0..).step_by(N) {
+        let mut bufs = bufs.iter_mut();
+        let suffixes: [&[u8]; N] =
+            std::array::from_fn(|l| digits(base + l as u64, bufs.next().unwrap()));
+        if let Some(l) = prefix.finalize_batch(&suffixes).iter().position(six_zeros) {
+            return base + l as u64;
+        }
+    }
+    unreachable!()
+}
+
+fn main() {
+    // Shaped like a real puzzle key, part 2 takes a few million candidates
+    let key = "ckczppom";
+    let answer = scalar_search(key);
+    assert_eq!(batch_search::<4>(key), answer);
+    assert_eq!(batch_search::<8>(key), answer);
+
+    let scalar = bench("scalar", 3, || scalar_search(key));
+    let four = bench("4 lanes", 3, || batch_search::<4>(key));
+    speedup(scalar, four);
+    let eight = bench("8 lanes", 3, || batch_search::<8>(key));
+    speedup(scalar, eight);
+}
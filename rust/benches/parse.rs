@@ -0,0 +1,55 @@
+mod common;
+
+use aoc::parse::numbers;
+use common::rng::Rng;
+use common::{bench, speedup};
+
+/// The original byte-at-a-time loop, kept as the baseline.
+fn scalar_sum(text: &[u8]) -> u64 {
+    let mut cursor = text;
+    let mut sum = 0u64;
+    while !cursor.is_empty() {
+        if cursor[0].is_ascii_digit() {
+            let mut acc = 0u64;
+            while !cursor.is_empty() && cursor[0].is_ascii_digit() {
+                acc = acc * 10 + (cursor[0] - b'0') as u64;
+                cursor = &cursor[1..];
+            }
+            sum = sum.wrapping_add(acc);
+            continue;
+        }
+        cursor = &cursor[1..];
+    }
+    sum
+}
+
+/// `count` numbers of 1 to `max_digits` digits, separated like typical puzzle input.
+fn synthetic(count: usize, max_digits: u64) -> String {
+    let mut rng = Rng::default();
+    let mut text = String::new();
+    for i in 0..count {
+        let state = rng.next_u64();
+        let digits = 1 + (state >> 40) % max_digits;
+        let value = state % 10u64.pow(digits as u32);
+        text.push_str(&value.to_string());
+        text.push_str(if i % 8 == 7 { "\n" } else { ", " });
+    }
+    text
+}
+
+fn main() {
+    for max_digits in [4, 8, 19] {
+        let text = synthetic(2_000_000, max_digits);
+        println!(
+            "up to {max_digits} digits, {:.1} MB",
+            text.len() as f64 / 1e6
+        );
+
+        let sum = || numbers::<u64, _>(&text).fold(0u64, u64::wrapping_add);
+        assert_eq!(scalar_sum(text.as_bytes()), sum());
+
+        let scalar = bench("  scalar", 10, || scalar_sum(text.as_bytes()));
+        let swar = bench("  numbers::<u64>", 10, sum);
+        speedup(scalar, swar);
+    }
+}
@@ -1,59 +1,31 @@
+use aoc::geom::{Direction, Point2};
+use aoc::grid::SparseGrid;
+
 fn parse(input: &str) -> &str {
     input.trim()
 }
 
-fn part1(input: &str) -> usize {
-    let mut pos = (0, 0);
-    let mut visited = std::collections::HashSet::new();
-    visited.insert(pos);
-
-    for ch in input.bytes() {
-        match ch {
-            b'^' => pos.1 += 1,
-            b'v' => pos.1 -= 1,
-            b'>' => pos.0 += 1,
-            b'<' => pos.0 -= 1,
-            _ => unreachable!(),
-        };
+fn visit(moves: impl Iterator<Item = u8>, visited: &mut SparseGrid<()>) {
+    let mut pos = Point2::ORIGIN;
+    visited.insert(pos, ());
 
-        visited.insert(pos);
+    for ch in moves {
+        pos += Direction::try_from(ch).unwrap();
+        visited.insert(pos, ());
     }
+}
 
+fn part1(input: &str) -> usize {
+    let mut visited = SparseGrid::new();
+    visit(input.bytes(), &mut visited);
     visited.len()
 }
 
 fn part2(input: &str) -> usize {
-    let mut real_pos = (0, 0);
-    let mut robo_pos = (0, 0);
-    let mut real = true;
-
-    let mut visited = std::collections::HashSet::new();
-    visited.insert(real_pos);
-
-    for ch in input.bytes() {
-        if real {
-            match ch {
-                b'^' => real_pos.1 += 1,
-                b'v' => real_pos.1 -= 1,
-                b'>' => real_pos.0 += 1,
-                b'<' => real_pos.0 -= 1,
-                _ => unreachable!(),
-            };
-            visited.insert(real_pos);
-            real = false;
-        } else {
-            match ch {
-                b'^' => robo_pos.1 += 1,
-                b'v' => robo_pos.1 -= 1,
-                b'>' => robo_pos.0 += 1,
-                b'<' => robo_pos.0 -= 1,
-                _ => unreachable!(),
-            };
-            visited.insert(robo_pos);
-            real = true;
-        }
-    }
-
+    let mut visited = SparseGrid::new();
+    // Santa and Robo-Santa take turns
+    visit(input.bytes().step_by(2), &mut visited);
+    visit(input.bytes().skip(1).step_by(2), &mut visited);
     visited.len()
 }
 
@@ -62,8 +34,8 @@ const INPUT: &str = include_str!("../../../inputs/2015/03.txt");
 fn main() {
     let input = parse(INPUT);
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    println!("Part 1: {}", part1(input));
+    println!("Part 2: {}", part2(input));
 }
 
 #[cfg(test)]
@@ -1,25 +1,42 @@
-use aoc::md5::hash;
+use aoc::md5::{Digest, Md5, digits};
+use aoc::search::parallel_first_chunked;
+
+// Candidates hashed per batch
+const LANES: usize = 8;
 
 fn parse(input: &str) -> &str {
     input.trim()
 }
 
-fn find_suffix(input: &str, start: u64, check: fn(&[u8]) -> bool) -> u64 {
-    for i in start.. {
-        if check(&hash(format!("{input}{i}"))) {
-            return i;
+fn find_suffix(input: &str, start: u64, check: fn(&Digest) -> bool) -> u64 {
+    // The key is hashed once, each candidate only adds its digits
+    let mut prefix = Md5::new();
+    prefix.update(input);
+
+    parallel_first_chunked(start.., 1024, |chunk| {
+        let mut bufs = [[0u8; 20]; LANES];
+        for base in chunk.clone().step_by(LANES) {
+            let mut bufs = bufs.iter_mut();
+            let suffixes: [&[u8]; LANES] =
+                std::array::from_fn(|l| digits(base + l as u64, bufs.next().unwrap()));
+
+            let digests = prefix.finalize_batch(&suffixes);
+            if let Some(l) = digests.iter().position(check) {
+                return Some(base + l as u64).filter(|i| chunk.contains(i));
+            }
         }
-    }
-    unreachable!()
+        None
+    })
+    .unwrap()
 }
 
 fn part1(input: &str) -> u64 {
-    let check_5_zeros = |bytes: &[u8]| matches!(bytes, [0, 0, 0..=0x0F, ..]);
+    let check_5_zeros = |digest: &Digest| digest.leading_zero_nibbles() >= 5;
     find_suffix(input, 0, check_5_zeros)
 }
 
 fn part2(input: &str, start: u64) -> u64 {
-    let check_6_zeros = |bytes: &[u8]| matches!(bytes, [0, 0, 0, ..]);
+    let check_6_zeros = |digest: &Digest| digest.leading_zero_nibbles() >= 6;
     find_suffix(input, start, check_6_zeros)
 }
 
@@ -77,8 +77,8 @@ const INPUT: &str = include_str!("../../../inputs/2015/05.txt");
 fn main() {
     let input = parse(INPUT);
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    println!("Part 1: {}", part1(input));
+    println!("Part 2: {}", part2(input));
 }
 
 #[cfg(test)]
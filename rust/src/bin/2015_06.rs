@@ -1,4 +1,6 @@
+use aoc::grid::BitGrid;
 use aoc::parse::{array, numbers};
+use aoc::sweep::{Op, Sweep};
 
 #[derive(PartialEq, Debug, Clone, Copy)]
 enum Action {
@@ -25,39 +27,30 @@ fn parse(input: &str) -> Vec<(Action, [usize; 4])> {
 }
 
 fn part1(input: &[(Action, [usize; 4])]) -> usize {
-    // 2D tensor repr
-    let mut grid = [false; 1000 * 1000];
+    let mut grid = BitGrid::new(1000, 1000);
 
     for &(action, [x1, y1, x2, y2]) in input {
-        grid.chunks_exact_mut(1000)
-            .skip(y1)
-            .take(y2 - y1 + 1)
-            .for_each(|row| match action {
-                Action::On => row[x1..=x2].fill(true),
-                Action::Off => row[x1..=x2].fill(false),
-                Action::Toggle => row[x1..=x2].iter_mut().for_each(|b| *b = !*b),
-            });
+        match action {
+            Action::On => grid.set_rect(x1..=x2, y1..=y2),
+            Action::Off => grid.clear_rect(x1..=x2, y1..=y2),
+            Action::Toggle => grid.toggle_rect(x1..=x2, y1..=y2),
+        }
     }
-    grid.iter().filter(|x| **x).count()
+    grid.count_ones()
 }
 
 fn part2(input: &[(Action, [usize; 4])]) -> usize {
-    // array causes stack overflow
-    let mut grid: Vec<usize> = vec![0; 1000 * 1000];
+    let mut sweep = Sweep::new();
 
     for &(action, [x1, y1, x2, y2]) in input {
-        grid.chunks_exact_mut(1000)
-            .skip(y1)
-            .take(y2 - y1 + 1)
-            .for_each(|row| match action {
-                Action::On => row[x1..=x2].iter_mut().for_each(|i| *i += 1),
-                Action::Off => row[x1..=x2]
-                    .iter_mut()
-                    .for_each(|i| *i = i.saturating_sub(1)),
-                Action::Toggle => row[x1..=x2].iter_mut().for_each(|i| *i += 2),
-            });
+        let op = match action {
+            Action::On => Op::Add(1),
+            Action::Off => Op::SaturatingSub(1),
+            Action::Toggle => Op::Add(2),
+        };
+        sweep.push(op, x1 as i64..=x2 as i64, y1 as i64..=y2 as i64);
     }
-    grid.iter().sum()
+    sweep.sum() as usize
 }
 
 const INPUT: &str = include_str!("../../../inputs/2015/06.txt");
@@ -1,100 +1,66 @@
-use std::collections::HashMap;
+use aoc::circuit::Circuit;
 
-#[derive(Clone, Debug)]
-enum Val {
-    Num(u16),
-    Wire(String),
+fn part1(input: &Circuit) -> u16 {
+    input.simulate().get(input.wire("a").unwrap())
 }
 
-#[derive(Clone, Debug)]
-enum Gate {
-    Eq(Val),
-    Not(Val),
-    And(Val, Val),
-    Or(Val, Val),
-    LShift(Val, u16),
-    RShift(Val, u16),
+fn part2(input: &Circuit, a: u16) -> u16 {
+    let mut sim = input.simulate();
+    sim.set(input.wire("b").unwrap(), a);
+    sim.get(input.wire("a").unwrap())
 }
 
-type Circuit = HashMap<String, Gate>;
-type Cache = HashMap<String, u16>;
-
-fn parse_val(s: &str) -> Val {
-    match s.parse::<u16>() {
-        Ok(n) => Val::Num(n),
-        Err(_) => Val::Wire(s.to_string()),
-    }
-}
+const INPUT: &str = include_str!("../../../inputs/2015/07.txt");
 
-fn parse(input: &str) -> Circuit {
-    input
-        .trim()
-        .lines()
-        .map(|line| {
-            let (expr, wire) = line.split_once(" -> ").unwrap();
-            let parts: Vec<&str> = expr.split_whitespace().collect();
-            let gate = match parts.as_slice() {
-                [a] => Gate::Eq(parse_val(a)),
-                ["NOT", a] => Gate::Not(parse_val(a)),
-                [a, "AND", b] => Gate::And(parse_val(a), parse_val(b)),
-                [a, "OR", b] => Gate::Or(parse_val(a), parse_val(b)),
-                [a, "LSHIFT", n] => Gate::LShift(parse_val(a), n.parse().unwrap()),
-                [a, "RSHIFT", n] => Gate::RShift(parse_val(a), n.parse().unwrap()),
-                _ => unreachable!(),
-            };
+fn main() {
+    let input = Circuit::parse(INPUT).unwrap();
 
-            (wire.to_string(), gate)
-        })
-        .collect()
+    let p1_result = part1(&input);
+    println!("Part 1: {}", p1_result);
+    println!("Part 2: {}", part2(&input, p1_result));
 }
 
-fn resolve(wire: &str, circuit: &Circuit, cache: &mut Cache) -> u16 {
-    if let Some(&val) = cache.get(wire) {
-        return val;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const INPUT: &str = "
+123 -> x
+456 -> y
+x AND y -> d
+x OR y -> e
+x LSHIFT 2 -> f
+y RSHIFT 2 -> g
+NOT x -> h
+NOT y -> i
+";
+
+    #[rstest]
+    #[case("d", 72)]
+    #[case("e", 507)]
+    #[case("f", 492)]
+    #[case("g", 114)]
+    #[case("h", 65412)]
+    #[case("i", 65079)]
+    #[case("x", 123)]
+    #[case("y", 456)]
+    fn test_resolve(#[case] wire: &str, #[case] expected: u16) {
+        let circuit = Circuit::parse(INPUT).unwrap();
+        let id = circuit.wire(wire).unwrap();
+        assert_eq!(circuit.simulate::<u16>().get(id), expected)
     }
 
-    let mut eval = |val: &Val| -> u16 {
-        match val {
-            Val::Num(n) => *n,
-            Val::Wire(w) => resolve(w, circuit, cache),
-        }
-    };
-
-    let gate = circuit
-        .get(wire)
-        .unwrap_or_else(|| panic!("Value {wire} should exist"));
-
-    let result = match gate {
-        Gate::Eq(val) => eval(val),
-        Gate::Not(val) => !eval(val),
-        Gate::And(a, b) => eval(a) & eval(b),
-        Gate::Or(a, b) => eval(a) | eval(b),
-        Gate::LShift(a, n) => eval(a) << n,
-        Gate::RShift(a, n) => eval(a) >> n,
-    };
-
-    cache.insert(wire.to_string(), result);
-    result
-}
-
-fn part1(input: &Circuit, cache: &mut Cache) -> u16 {
-    resolve("a", input, cache)
-}
-
-fn part2(input: &Circuit, cache: &mut Cache) -> u16 {
-    resolve("a", input, cache)
-}
-
-const INPUT: &str = include_str!("../../../inputs/2015/07.txt");
-
-fn main() {
-    let input = parse(INPUT);
-    let mut cache = HashMap::new();
-
-    let p1_result = part1(&input, &mut cache);
-    println!("Part 1: {}", p1_result);
+    #[test]
+    fn test_part2() {
+        let circuit = Circuit::parse("b -> a\n1 -> b").unwrap();
+        assert_eq!(part1(&circuit), 1);
+        assert_eq!(part2(&circuit, 5), 5);
+    }
 
-    cache.clear();
-    cache.insert("b".to_string(), p1_result);
-    println!("Part 2: {}", part2(&input, &mut cache));
+    #[test]
+    #[should_panic(expected = "Wires should not form a cycle: a -> b")]
+    fn test_cycle() {
+        part1(&Circuit::parse("b -> a\nNOT a -> b").unwrap());
+    }
 }
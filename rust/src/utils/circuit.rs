@@ -0,0 +1,492 @@
+use std::fmt::{self, Debug};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::graph::{reachable, topo_order};
+use crate::intern::Interner;
+use crate::parse::{Token, tokens};
+
+/// Signal types a circuit can run on. Shifts past the width give zero.
+pub trait Word:
+    Copy
+    + Debug
+    + Eq
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+{
+    /// A literal from the netlist, truncated to the width.
+    fn literal(n: u64) -> Self;
+    fn shl(self, n: u32) -> Self;
+    fn shr(self, n: u32) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            fn literal(n: u64) -> Self {
+                n as $t
+            }
+
+            fn shl(self, n: u32) -> Self {
+                self.checked_shl(n).unwrap_or(0)
+            }
+
+            fn shr(self, n: u32) -> Self {
+                self.checked_shr(n).unwrap_or(0)
+            }
+        }
+    )*};
+}
+
+impl_word!(u8, u16, u32, u64, u128);
+
+/// A single bit: literals are true when non-zero.
+impl Word for bool {
+    fn literal(n: u64) -> Self {
+        n != 0
+    }
+
+    fn shl(self, n: u32) -> Self {
+        self && n == 0
+    }
+
+    fn shr(self, n: u32) -> Self {
+        self && n == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// Negative literals are kept in two's complement.
+    Const(u64),
+    Wire(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Copy(Operand),
+    Not(Operand),
+    And(Operand, Operand),
+    Or(Operand, Operand),
+    Xor(Operand, Operand),
+    LShift(Operand, u32),
+    RShift(Operand, u32),
+}
+
+impl Gate {
+    pub fn operands(&self) -> impl Iterator<Item = Operand> {
+        let (a, b) = match *self {
+            Gate::Copy(a) | Gate::Not(a) | Gate::LShift(a, _) | Gate::RShift(a, _) => (a, None),
+            Gate::And(a, b) | Gate::Or(a, b) | Gate::Xor(a, b) => (a, Some(b)),
+        };
+        std::iter::once(a).chain(b)
+    }
+
+    /// Wires this gate reads.
+    pub fn inputs(&self) -> impl Iterator<Item = u32> {
+        self.operands().filter_map(|operand| match operand {
+            Operand::Const(_) => None,
+            Operand::Wire(w) => Some(w),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Expected {} on line {}", self.expected, self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// `token` as a `T`, or `None` if it isn't a number or doesn't fit.
+fn number<T: TryFrom<i128>>(token: &Token) -> Option<T> {
+    match token {
+        // Short enough that reading it into an i128 can't overflow
+        Token::Number(s) if s.trim_start_matches('-').len() <= 38 => {
+            token.number::<i128>()?.try_into().ok()
+        }
+        _ => None,
+    }
+}
+
+/// A netlist of gates, each driving one wire, with wires interned to dense ids.
+///
+/// Lines look like `x AND y -> z`, `NOT x -> y`, `x LSHIFT 2 -> y`, `123 -> x`
+/// or `x: 1`.
+#[derive(Clone, Debug)]
+pub struct Circuit<'a> {
+    wires: Interner<'a, u32>,
+    // Indexed by wire id
+    drivers: Vec<Option<Gate>>,
+    fanout: Vec<Vec<u32>>,
+}
+
+impl<'a> Circuit<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, ParseError> {
+        let mut wires = Interner::new();
+        let mut drivers = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = |expected| ParseError {
+                line: i + 1,
+                expected,
+            };
+            let mut operand = |token: &Token<'a>| match *token {
+                Token::Number(_) => number::<u64>(token)
+                    .or_else(|| number::<i64>(token).map(|n| n as u64))
+                    .map(Operand::Const)
+                    .ok_or(error("a constant that fits in 64 bits")),
+                Token::Word(w) => Ok(Operand::Wire(wires.intern(w))),
+            };
+            let shift = |token: &Token| number::<u32>(token).ok_or(error("a shift amount"));
+
+            let parts: Vec<Token> = tokens(line).collect();
+            // `tokens` drops the `:`, so check it sits right after the wire
+            let assigned = line
+                .split_once(':')
+                .map(|(wire, _)| tokens(wire).count() == 1);
+            let (wire, gate) = match (line.contains("->"), assigned, parts.as_slice()) {
+                (false, Some(true), [wire, value]) => (wire, Gate::Copy(operand(value)?)),
+                (true, None, [expr @ .., wire]) => {
+                    let gate = match expr {
+                        [a] => Gate::Copy(operand(a)?),
+                        [Token::Word("NOT"), a] => Gate::Not(operand(a)?),
+                        [a, Token::Word("AND"), b] => Gate::And(operand(a)?, operand(b)?),
+                        [a, Token::Word("OR"), b] => Gate::Or(operand(a)?, operand(b)?),
+                        [a, Token::Word("XOR"), b] => Gate::Xor(operand(a)?, operand(b)?),
+                        [a, Token::Word("LSHIFT"), n] => Gate::LShift(operand(a)?, shift(n)?),
+                        [a, Token::Word("RSHIFT"), n] => Gate::RShift(operand(a)?, shift(n)?),
+                        _ => return Err(error("a gate")),
+                    };
+                    (wire, gate)
+                }
+                _ => return Err(error("a gate")),
+            };
+
+            let Token::Word(wire) = wire else {
+                return Err(error("a wire name"));
+            };
+            let id = wires.intern(wire) as usize;
+            if drivers.len() <= id {
+                drivers.resize(id + 1, None);
+            }
+            drivers[id] = Some(gate);
+        }
+
+        drivers.resize(wires.len(), None);
+        let mut circuit = Self {
+            wires,
+            drivers,
+            fanout: Vec::new(),
+        };
+        circuit.link();
+        Ok(circuit)
+    }
+
+    /// Rebuilds the fanout lists from the drivers.
+    fn link(&mut self) {
+        let mut fanout = vec![Vec::new(); self.drivers.len()];
+        for (wire, gate) in self.gates() {
+            for input in gate.inputs() {
+                fanout[input as usize].push(wire);
+            }
+        }
+        self.fanout = fanout;
+    }
+
+    pub fn wire(&self, name: &str) -> Option<u32> {
+        self.wires.get(name)
+    }
+
+    pub fn name(&self, wire: u32) -> &'a str {
+        self.wires.name(wire)
+    }
+
+    /// Every wire name, indexed by id.
+    pub fn names(&self) -> &[&'a str] {
+        self.wires.names()
+    }
+
+    /// The gate driving `wire`, if any.
+    pub fn driver(&self, wire: u32) -> Option<&Gate> {
+        self.drivers[wire as usize].as_ref()
+    }
+
+    /// Wires driven by gates reading `wire`.
+    pub fn fanout(&self, wire: u32) -> &[u32] {
+        &self.fanout[wire as usize]
+    }
+
+    /// Every gate with the wire it drives.
+    pub fn gates(&self) -> impl Iterator<Item = (u32, &Gate)> {
+        (0..)
+            .zip(&self.drivers)
+            .filter_map(|(wire, gate)| Some((wire, gate.as_ref()?)))
+    }
+
+    /// Swaps the gates driving `a` and `b`.
+    pub fn swap_outputs(&mut self, a: u32, b: u32) {
+        self.drivers.swap(a as usize, b as usize);
+        self.link();
+    }
+
+    pub fn simulate<W: Word>(&self) -> Simulation<'_, 'a, W> {
+        Simulation {
+            circuit: self,
+            values: vec![None; self.drivers.len()],
+            overrides: vec![None; self.drivers.len()],
+        }
+    }
+}
+
+/// Signals of a circuit, computed on demand and kept until something they
+/// depend on changes.
+#[derive(Clone, Debug)]
+pub struct Simulation<'c, 'a, W> {
+    circuit: &'c Circuit<'a>,
+    // Both indexed by wire id
+    values: Vec<Option<W>>,
+    overrides: Vec<Option<W>>,
+}
+
+impl<W: Word> Simulation<'_, '_, W> {
+    /// The signal on `wire`, or the wires of a cycle it depends on.
+    pub fn try_get(&mut self, wire: u32) -> Result<W, Vec<u32>> {
+        let circuit = self.circuit;
+        let deps = |&wire: &u32| circuit.driver(wire).into_iter().flat_map(Gate::inputs);
+        for w in topo_order([wire], deps, |&w| self.values[w as usize].is_some())? {
+            self.values[w as usize] = Some(self.eval(w));
+        }
+        Ok(self.values[wire as usize].unwrap())
+    }
+
+    /// Output of the gate driving `wire`, once its inputs are known.
+    fn eval(&self, wire: u32) -> W {
+        let val = |operand| match operand {
+            Operand::Const(n) => W::literal(n),
+            Operand::Wire(w) => self.values[w as usize].unwrap(),
+        };
+        let gate = self
+            .circuit
+            .driver(wire)
+            .unwrap_or_else(|| panic!("Wire {} should be driven", self.circuit.name(wire)));
+        match *gate {
+            Gate::Copy(a) => val(a),
+            Gate::Not(a) => !val(a),
+            Gate::And(a, b) => val(a) & val(b),
+            Gate::Or(a, b) => val(a) | val(b),
+            Gate::Xor(a, b) => val(a) ^ val(b),
+            Gate::LShift(a, n) => val(a).shl(n),
+            Gate::RShift(a, n) => val(a).shr(n),
+        }
+    }
+
+    /// The signal on `wire`. Panics if it depends on a cycle.
+    pub fn get(&mut self, wire: u32) -> W {
+        self.try_get(wire).unwrap_or_else(|cycle| {
+            let names: Vec<_> = cycle.iter().map(|&w| self.circuit.name(w)).collect();
+            panic!("Wires should not form a cycle: {}", names.join(" -> "))
+        })
+    }
+
+    /// Forces `wire` to `value`, ignoring its gate, until [`Self::release`].
+    pub fn set(&mut self, wire: u32, value: W) {
+        self.overrides[wire as usize] = Some(value);
+        self.invalidate(wire);
+    }
+
+    /// Lets `wire` follow its gate again.
+    pub fn release(&mut self, wire: u32) {
+        if self.overrides[wire as usize].take().is_some() {
+            self.invalidate(wire);
+        }
+    }
+
+    /// Drops the values depending on `wire`, so only they are computed again.
+    fn invalidate(&mut self, wire: u32) {
+        for w in reachable(wire, |&w| self.circuit.fanout(w).iter().copied()) {
+            self.values[w as usize] = self.overrides[w as usize];
+        }
+    }
+}
+
+impl Simulation<'_, '_, bool> {
+    /// Reads the wires named `prefix` followed by a bit index, like `z00`, as a
+    /// number.
+    pub fn read_bits(&mut self, prefix: &str) -> Result<u64, Vec<u32>> {
+        let mut n = 0;
+        for (bit, wire) in self.bit_wires(prefix) {
+            n |= (self.try_get(wire)? as u64) << bit;
+        }
+        Ok(n)
+    }
+
+    /// Sets the wires named `prefix` followed by a bit index to the bits of `n`.
+    pub fn write_bits(&mut self, prefix: &str, n: u64) {
+        for (bit, wire) in self.bit_wires(prefix) {
+            self.set(wire, n >> bit & 1 == 1);
+        }
+    }
+
+    fn bit_wires(&self, prefix: &str) -> Vec<(u32, u32)> {
+        (0..)
+            .zip(self.circuit.names())
+            .filter_map(|(wire, name)| {
+                let bit = name.strip_prefix(prefix)?.parse().ok()?;
+                Some((bit, wire))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const EXAMPLE: &str = "
+123 -> x
+456 -> y
+x AND y -> d
+x OR y -> e
+x LSHIFT 2 -> f
+y RSHIFT 2 -> g
+NOT x -> h
+NOT y -> i
+";
+
+    const ADDER: &str = "
+x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02
+";
+
+    #[rstest]
+    #[case("d", 72, 72)]
+    #[case("e", 507, 507)]
+    #[case("f", 492, 492)]
+    #[case("g", 114, 114)]
+    #[case("h", 65412, !123)]
+    #[case("i", 65079, !456)]
+    fn test_widths(#[case] wire: &str, #[case] narrow: u16, #[case] wide: u64) {
+        let circuit = Circuit::parse(EXAMPLE).unwrap();
+        let wire = circuit.wire(wire).unwrap();
+        assert_eq!(circuit.simulate::<u16>().get(wire), narrow);
+        assert_eq!(circuit.simulate::<u64>().get(wire), wide);
+    }
+
+    #[test]
+    fn test_override_cone() {
+        let circuit = Circuit::parse(EXAMPLE).unwrap();
+        let [x, d, g, h] = ["x", "d", "g", "h"].map(|w| circuit.wire(w).unwrap());
+        let mut sim = circuit.simulate::<u16>();
+        (0..circuit.names().len() as u32).for_each(|w| _ = sim.get(w));
+
+        sim.set(x, 0xff);
+        // Only x's cone is recomputed, y and its fanout stay
+        assert_eq!(sim.values.iter().flatten().count(), 4);
+        assert_eq!(
+            (sim.get(d), sim.get(g), sim.get(h)),
+            (0xff & 456, 114, 0xff00)
+        );
+
+        sim.release(x);
+        assert_eq!(sim.get(h), 65412);
+    }
+
+    #[test]
+    fn test_bits() {
+        let circuit = Circuit::parse(ADDER).unwrap();
+        let mut sim = circuit.simulate::<bool>();
+        assert_eq!(sim.read_bits("x"), Ok(7));
+        assert_eq!(sim.read_bits("z"), Ok(4));
+        sim.write_bits("y", 0b111);
+        assert_eq!(sim.read_bits("z"), Ok(0b101));
+        assert_eq!(circuit.gates().count(), 9);
+        assert_eq!(
+            circuit.fanout(circuit.wire("x01").unwrap()),
+            &[circuit.wire("z01").unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_swap_outputs() {
+        let mut circuit =
+            Circuit::parse("a AND b -> c\nc OR d -> e\n1 -> a\n1 -> b\n0 -> d").unwrap();
+        let [c, e] = ["c", "e"].map(|w| circuit.wire(w).unwrap());
+        assert!(circuit.simulate::<bool>().get(e));
+
+        circuit.swap_outputs(c, e);
+        assert_eq!(
+            circuit.driver(e),
+            Some(&Gate::And(Operand::Wire(0), Operand::Wire(1)))
+        );
+        assert_eq!(circuit.simulate::<bool>().try_get(e), Ok(true));
+        assert_eq!(circuit.simulate::<bool>().try_get(c), Err(vec![c]));
+    }
+
+    #[test]
+    fn test_constants() {
+        let circuit =
+            Circuit::parse("-1 -> a\n18446744073709551615 -> b\na RSHIFT 60 -> c").unwrap();
+        let [a, b, c] = ["a", "b", "c"].map(|w| circuit.wire(w).unwrap());
+        let mut sim = circuit.simulate::<u16>();
+        assert_eq!((sim.get(a), sim.get(b), sim.get(c)), (0xffff, 0xffff, 0));
+        assert_eq!(circuit.simulate::<u64>().get(c), 0xf);
+    }
+
+    #[rstest]
+    #[case(
+        "1 -> a\n\n18446744073709551616 -> b",
+        3,
+        "a constant that fits in 64 bits"
+    )]
+    #[case("-9223372036854775809 -> a", 1, "a constant that fits in 64 bits")]
+    #[case(
+        "1234567890123456789012345678901234567890 -> a",
+        1,
+        "a constant that fits in 64 bits"
+    )]
+    #[case("a LSHIFT -1 -> b", 1, "a shift amount")]
+    #[case("a LSHIFT b -> c", 1, "a shift amount")]
+    #[case("a NAND b -> c", 1, "a gate")]
+    #[case("a: b c", 1, "a gate")]
+    #[case("NOT x", 1, "a gate")]
+    #[case("y z", 1, "a gate")]
+    #[case("y z: 1", 1, "a gate")]
+    #[case("x: 1 -> y", 1, "a gate")]
+    #[case("1: 2", 1, "a wire name")]
+    #[case("a -> 2", 1, "a wire name")]
+    fn test_parse_errors(#[case] text: &str, #[case] line: usize, #[case] expected: &'static str) {
+        assert_eq!(
+            Circuit::parse(text).unwrap_err(),
+            ParseError { line, expected }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Wire b should be driven")]
+    fn test_undriven() {
+        let circuit = Circuit::parse("b -> a").unwrap();
+        circuit.simulate::<u16>().get(circuit.wire("a").unwrap());
+    }
+}
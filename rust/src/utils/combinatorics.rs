@@ -0,0 +1,456 @@
+/// Most items a permutation, combination or partition can have, so buffers
+/// fit on the stack. Enumerating anywhere near this many would never end anyway.
+pub const MAX_LEN: usize = 32;
+
+/// An iterator whose items borrow from itself, valid until the next call, so
+/// the enumerations here reuse one buffer instead of allocating per item. Loop
+/// with `while let Some(item) = iter.next()`, or pass a callback to the
+/// matching `for_each_*` function.
+pub trait LendingIterator {
+    type Item<'a>
+    where
+        Self: 'a;
+
+    fn next(&mut self) -> Option<Self::Item<'_>>;
+
+    fn count(mut self) -> usize
+    where
+        Self: Sized,
+    {
+        let mut n = 0;
+        while self.next().is_some() {
+            n += 1;
+        }
+        n
+    }
+}
+
+/// Every ordering of a slice, permuted in place by Heap's algorithm so each
+/// step is a single swap. The slice is left in the last ordering.
+pub struct Permutations<'a, T> {
+    items: &'a mut [T],
+    counters: [u8; MAX_LEN],
+    i: usize,
+    started: bool,
+}
+
+pub fn permutations<T>(items: &mut [T]) -> Permutations<'_, T> {
+    assert!(items.len() <= MAX_LEN, "Too many items to permute");
+    Permutations {
+        items,
+        counters: [0; MAX_LEN],
+        i: 1,
+        started: false,
+    }
+}
+
+impl<T> LendingIterator for Permutations<'_, T> {
+    type Item<'a>
+        = &'a [T]
+    where
+        Self: 'a;
+
+    fn next(&mut self) -> Option<&[T]> {
+        if !self.started {
+            self.started = true;
+            return Some(self.items);
+        }
+        while self.i < self.items.len() {
+            let c = &mut self.counters[self.i];
+            if (*c as usize) < self.i {
+                let j = if self.i.is_multiple_of(2) {
+                    0
+                } else {
+                    *c as usize
+                };
+                *c += 1;
+                self.items.swap(j, self.i);
+                self.i = 1;
+                return Some(self.items);
+            }
+            *c = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+pub fn for_each_permutation<T>(items: &mut [T], mut f: impl FnMut(&[T])) {
+    let mut iter = permutations(items);
+    while let Some(p) = iter.next() {
+        f(p);
+    }
+}
+
+/// Every `k`-element subset of `0..n` as ascending indices, in lexicographic
+/// order.
+pub struct Combinations {
+    n: usize,
+    k: usize,
+    indices: [usize; MAX_LEN],
+    started: bool,
+}
+
+pub fn combinations(n: usize, k: usize) -> Combinations {
+    assert!(k <= MAX_LEN, "Too many items to combine");
+    Combinations {
+        n,
+        k,
+        indices: std::array::from_fn(|i| i),
+        started: false,
+    }
+}
+
+impl LendingIterator for Combinations {
+    type Item<'a> = &'a [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        let (n, k) = (self.n, self.k);
+        if k > n {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(&self.indices[..k]);
+        }
+        // The last index that can still move right
+        let i = (0..k).rev().find(|&i| self.indices[i] < n - k + i)?;
+        self.indices[i] += 1;
+        for j in i + 1..k {
+            self.indices[j] = self.indices[j - 1] + 1;
+        }
+        Some(&self.indices[..k])
+    }
+}
+
+pub fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(&[usize])) {
+    let mut iter = combinations(n, k);
+    while let Some(c) = iter.next() {
+        f(c);
+    }
+}
+
+/// Every subset of `n` items as a bitmask, from the empty set up.
+pub fn subsets(n: u32) -> impl Iterator<Item = u64> {
+    assert!(n < 64, "Subsets should fit in a u64 mask");
+    0..1 << n
+}
+
+/// Every subset of `n` items with exactly `k` of them, in increasing order,
+/// by Gosper's hack.
+pub fn subsets_of_size(n: u32, k: u32) -> impl Iterator<Item = u64> {
+    assert!(n < 64, "Subsets should fit in a u64 mask");
+    let first = (k <= n).then(|| (1u64 << k) - 1);
+    std::iter::successors(first, move |&mask| {
+        if mask == 0 {
+            return None;
+        }
+        let low = mask & mask.wrapping_neg();
+        let ripple = mask + low;
+        let next = (((ripple ^ mask) >> 2) / low) | ripple;
+        (next < 1 << n).then_some(next)
+    })
+}
+
+/// Every subset of `mask`, from `mask` itself down to the empty set.
+pub fn submasks(mask: u64) -> impl Iterator<Item = u64> {
+    let mut next = Some(mask);
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = (current != 0).then(|| (current - 1) & mask);
+        Some(current)
+    })
+}
+
+/// Indices of the set bits of `mask`, lowest first.
+pub fn bits(mut mask: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let i = mask.trailing_zeros() as usize;
+            mask &= mask - 1;
+            i
+        })
+    })
+}
+
+/// Every way to write `n` as an ordered sum of `k` parts, each at least `min`,
+/// in lexicographic order. 2015 day 15 wants `compositions(100, 4, 0)`.
+pub struct Compositions {
+    k: usize,
+    min: u64,
+    parts: [u64; MAX_LEN],
+    started: bool,
+}
+
+pub fn compositions(n: u64, k: usize, min: u64) -> Compositions {
+    assert!(k <= MAX_LEN, "Too many parts");
+    let mut parts = [min; MAX_LEN];
+    // Nothing fits if even the minimums overshoot, which `started` covers
+    let fits = k as u64 * min <= n && (k > 0 || n == 0);
+    if k > 0 && fits {
+        parts[k - 1] = n - (k as u64 - 1) * min;
+    }
+    Compositions {
+        k,
+        min,
+        parts,
+        started: !fits,
+    }
+}
+
+impl LendingIterator for Compositions {
+    type Item<'a> = &'a [u64];
+
+    fn next(&mut self) -> Option<&[u64]> {
+        let (k, min) = (self.k, self.min);
+        if !self.started {
+            self.started = true;
+            return Some(&self.parts[..k]);
+        }
+        if k < 2 {
+            return None;
+        }
+        let parts = &mut self.parts;
+        if parts[k - 1] > min {
+            // Move one unit from the last part to the one before it
+            parts[k - 2] += 1;
+            parts[k - 1] -= 1;
+        } else {
+            // Carry: reset the rightmost raised part into the last one
+            let i = (0..k - 1).rev().find(|&i| parts[i] > min)?;
+            if i == 0 {
+                return None;
+            }
+            parts[k - 1] = parts[i] - 1;
+            parts[i] = min;
+            parts[i - 1] += 1;
+        }
+        Some(&parts[..k])
+    }
+}
+
+pub fn for_each_composition(n: u64, k: usize, min: u64, mut f: impl FnMut(&[u64])) {
+    let mut iter = compositions(n, k, min);
+    while let Some(c) = iter.next() {
+        f(c);
+    }
+}
+
+/// Every way to write `n` as an unordered sum of exactly `k` positive parts,
+/// each given largest part first, by Knuth's Algorithm H.
+pub struct Partitions {
+    k: usize,
+    parts: [u64; MAX_LEN],
+    started: bool,
+}
+
+pub fn partitions(n: u64, k: usize) -> Partitions {
+    assert!(k <= MAX_LEN, "Too many parts");
+    let fits = if k == 0 { n == 0 } else { k as u64 <= n };
+    let mut parts = [1; MAX_LEN];
+    if k > 0 && fits {
+        parts[0] = n - k as u64 + 1;
+    }
+    Partitions {
+        k,
+        parts,
+        started: !fits,
+    }
+}
+
+impl LendingIterator for Partitions {
+    type Item<'a> = &'a [u64];
+
+    fn next(&mut self) -> Option<&[u64]> {
+        let k = self.k;
+        if !self.started {
+            self.started = true;
+            return Some(&self.parts[..k]);
+        }
+        if k < 2 {
+            return None;
+        }
+        let a = &mut self.parts;
+        if a[1] + 1 < a[0] {
+            a[0] -= 1;
+            a[1] += 1;
+            return Some(&a[..k]);
+        }
+        // Find the first part that can grow, then refill everything before it
+        let mut j = 2;
+        let mut s = a[0] + a[1] - 1;
+        while j < k && a[j] + 1 >= a[0] {
+            s += a[j];
+            j += 1;
+        }
+        if j >= k {
+            return None;
+        }
+        let x = a[j] + 1;
+        a[j] = x;
+        for part in &mut a[1..j] {
+            *part = x;
+            s -= x;
+        }
+        a[0] = s;
+        Some(&a[..k])
+    }
+}
+
+pub fn for_each_partition(n: u64, k: usize, mut f: impl FnMut(&[u64])) {
+    let mut iter = partitions(n, k);
+    while let Some(p) = iter.next() {
+        f(p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    fn collect(for_each: impl FnOnce(&mut dyn FnMut(&[u64]))) -> Vec<Vec<u64>> {
+        let mut all = Vec::new();
+        for_each(&mut |item| all.push(item.to_vec()));
+        all
+    }
+
+    fn binomial(n: u64, k: u64) -> u64 {
+        (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+    }
+
+    #[test]
+    fn test_permutations() {
+        for n in 0..=7 {
+            let mut items: Vec<u8> = (0..n).collect();
+            let mut seen = HashSet::new();
+            for_each_permutation(&mut items, |p| assert!(seen.insert(p.to_vec())));
+            assert_eq!(seen.len(), (1..=n as usize).product::<usize>());
+        }
+        let mut items = ['a', 'b', 'c'];
+        let mut iter = permutations(&mut items);
+        assert_eq!(iter.next(), Some(&['a', 'b', 'c'][..]));
+        assert_eq!(iter.next(), Some(&['b', 'a', 'c'][..]));
+    }
+
+    #[test]
+    fn test_combinations() {
+        for n in 0..8 {
+            for k in 0..=n + 1 {
+                let mut seen = Vec::new();
+                for_each_combination(n, k, |c| {
+                    assert!(c.windows(2).all(|w| w[0] < w[1]) && c.iter().all(|&i| i < n));
+                    seen.push(c.to_vec());
+                });
+                assert!(seen.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(seen.len() as u64, binomial(n as u64, k as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_exhausted_stays_exhausted() {
+        fn check<I: LendingIterator>(mut iter: I) {
+            while iter.next().is_some() {}
+            assert!(iter.next().is_none() && iter.next().is_none());
+        }
+        for (n, k) in [(0, 0), (3, 2), (2, 3), (0, 1)] {
+            check(combinations(n, k));
+            check(compositions(n as u64, k, 1));
+            check(partitions(n as u64, k));
+        }
+        check(permutations(&mut [1, 2, 3]));
+        check(permutations::<u8>(&mut []));
+    }
+
+    #[test]
+    fn test_subsets() {
+        assert_eq!(subsets(3).count(), 8);
+        for n in 0..10 {
+            for k in 0..=n + 1 {
+                let expected: Vec<u64> = subsets(n).filter(|m| m.count_ones() == k).collect();
+                assert_eq!(subsets_of_size(n, k).collect::<Vec<_>>(), expected);
+            }
+        }
+        assert_eq!(
+            submasks(0b1010).collect::<Vec<_>>(),
+            vec![0b1010, 0b1000, 0b10, 0]
+        );
+        assert_eq!(bits(0b1001_0010).collect::<Vec<_>>(), vec![1, 4, 7]);
+
+        // 2015 day 17 example: 4 ways to hold 25 liters
+        let containers = [20, 15, 10, 5, 5];
+        let ways = subsets(5)
+            .filter(|&m| bits(m).map(|i| containers[i]).sum::<u32>() == 25)
+            .count();
+        assert_eq!(ways, 4);
+    }
+
+    #[rstest]
+    #[case(2, 2, 0, vec![vec![0, 2], vec![1, 1], vec![2, 0]])]
+    #[case(1, 3, 0, vec![vec![0, 0, 1], vec![0, 1, 0], vec![1, 0, 0]])]
+    #[case(5, 2, 2, vec![vec![2, 3], vec![3, 2]])]
+    #[case(3, 1, 0, vec![vec![3]])]
+    #[case(0, 0, 0, vec![vec![]])]
+    #[case(3, 0, 0, vec![])]
+    #[case(3, 2, 2, vec![])]
+    fn test_compositions(
+        #[case] n: u64,
+        #[case] k: usize,
+        #[case] min: u64,
+        #[case] expected: Vec<Vec<u64>>,
+    ) {
+        assert_eq!(collect(|f| for_each_composition(n, k, min, f)), expected);
+    }
+
+    #[test]
+    fn test_composition_counts() {
+        for n in 0..10 {
+            for k in 1..5 {
+                let all = collect(|f| for_each_composition(n, k, 0, f));
+                assert_eq!(all.len() as u64, binomial(n + k as u64 - 1, k as u64 - 1));
+                assert!(all.iter().all(|c| c.iter().sum::<u64>() == n));
+                assert!(all.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(
+                    compositions(n, k, 1).count() as u64,
+                    if n < k as u64 {
+                        0
+                    } else {
+                        binomial(n - 1, k as u64 - 1)
+                    }
+                );
+            }
+        }
+        assert_eq!(compositions(100, 4, 0).count(), 176851);
+    }
+
+    #[test]
+    fn test_partitions() {
+        assert_eq!(
+            collect(|f| for_each_partition(8, 3, f)),
+            vec![
+                vec![6, 1, 1],
+                vec![5, 2, 1],
+                vec![4, 3, 1],
+                vec![4, 2, 2],
+                vec![3, 3, 2]
+            ]
+        );
+        for n in 0..16 {
+            for k in 0..6 {
+                // Brute force: sorted compositions into positive parts
+                let expected: HashSet<Vec<u64>> = collect(|f| for_each_composition(n, k, 1, f))
+                    .into_iter()
+                    .map(|mut c| {
+                        c.sort_unstable_by(|a, b| b.cmp(a));
+                        c
+                    })
+                    .collect();
+                let all = collect(|f| for_each_partition(n, k, f));
+                assert_eq!(all.len(), expected.len(), "partitions({n}, {k})");
+                assert_eq!(all.into_iter().collect::<HashSet<_>>(), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// All of these follow `step` from `start` and report the cycle the sequence
+// ends up in as `(mu, lambda)`: the index of the first state on the cycle and
+// the cycle length. The sequence must eventually repeat.
+
+/// Floyd's tortoise and hare, keeping only two states at a time.
+pub fn floyd<S: Clone + PartialEq>(start: S, mut step: impl FnMut(&S) -> S) -> (usize, usize) {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut mu = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+
+    let mut lambda = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        lambda += 1;
+    }
+    (mu, lambda)
+}
+
+/// Brent's algorithm, keeping only two states and usually stepping less than
+/// [`floyd`].
+pub fn brent<S: Clone + PartialEq>(start: S, mut step: impl FnMut(&S) -> S) -> (usize, usize) {
+    let (mut power, mut lambda) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+        hare = step(&hare);
+        lambda += 1;
+    }
+
+    let mut mu = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..lambda {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+    (mu, lambda)
+}
+
+/// Remembers every state, stepping through the cycle once.
+pub fn find_cycle<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S) -> (usize, usize) {
+    find_cycle_by(start, step, S::clone)
+}
+
+/// Like [`find_cycle`], but remembers `key` of each state, like a hash or a
+/// normalized form, instead of the state itself.
+pub fn find_cycle_by<S, K: Hash + Eq>(
+    start: S,
+    step: impl FnMut(&S) -> S,
+    key: impl FnMut(&S) -> K,
+) -> (usize, usize) {
+    match walk(start, step, key, usize::MAX) {
+        Walk::Cycle(mu, lambda, _) => (mu, lambda),
+        Walk::Done(_) => unreachable!(),
+    }
+}
+
+enum Walk<S> {
+    /// Cycle found, with the state at `mu + lambda`.
+    Cycle(usize, usize, S),
+    /// Reached step `n` before finding a cycle.
+    Done(S),
+}
+
+fn walk<S, K: Hash + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+    n: usize,
+) -> Walk<S> {
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0..n {
+        if let Some(mu) = seen.insert(key(&state), i) {
+            return Walk::Cycle(mu, i - mu, state);
+        }
+        state = step(&state);
+    }
+    Walk::Done(state)
+}
+
+/// The state after `n` steps, skipping whole cycles once one is found.
+pub fn extrapolate<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    extrapolate_by(start, step, S::clone, n)
+}
+
+/// Like [`extrapolate`], but remembers `key` of each state instead of the state.
+pub fn extrapolate_by<S, K: Hash + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> S,
+    key: impl FnMut(&S) -> K,
+    n: usize,
+) -> S {
+    match walk(start, &mut step, key, n) {
+        Walk::Done(state) => state,
+        Walk::Cycle(mu, lambda, mut state) => {
+            // `state` is at mu + lambda, the same place as mu
+            for _ in 0..(n - mu - lambda) % lambda {
+                state = step(&state);
+            }
+            state
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use rstest::rstest;
+
+    /// Cycle of a table-driven sequence, found by checking every pair.
+    fn brute_force(table: &[usize], start: usize) -> (usize, usize) {
+        let seq: Vec<_> = std::iter::successors(Some(start), |&x| Some(table[x]))
+            .take(table.len() + 1)
+            .collect();
+        (0..seq.len())
+            .find_map(|j| (0..j).find(|&i| seq[i] == seq[j]).map(|i| (i, j - i)))
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case(0, (5, 6))]
+    #[case(7, (0, 6))]
+    #[case(12, (1, 6))]
+    fn test_known(#[case] start: u32, #[case] expected: (usize, usize)) {
+        // 5..=10 loop, everything above drops to 5
+        let step = |&x: &u32| if x < 10 { x + 1 } else { 5 };
+        let key = |&x: &u32| x % 6;
+        assert_eq!(floyd(start, step), expected);
+        assert_eq!(brent(start, step), expected);
+        assert_eq!(find_cycle(start, step), expected);
+        // A coarser key than the state still works if it agrees on the cycle
+        assert_eq!(find_cycle_by(5, step, key), (0, 6));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::default();
+        for len in 1..60 {
+            let table: Vec<usize> = (0..len).map(|_| rng.below(len as u64) as usize).collect();
+            let step = |&x: &usize| table[x];
+            let expected = brute_force(&table, 0);
+            assert_eq!(floyd(0, step), expected);
+            assert_eq!(brent(0, step), expected);
+            assert_eq!(find_cycle(0, step), expected);
+            for n in 0..3 * len {
+                let direct = (0..n).fold(0, |x, _| table[x]);
+                assert_eq!(extrapolate(0, step, n), direct);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extrapolate_large_state() {
+        // A rotating buffer only identified by its first bytes
+        let start: Vec<u8> = (0..=200).collect();
+        let step = |v: &Vec<u8>| {
+            let mut next = v.clone();
+            next.rotate_left(1);
+            next
+        };
+        let key = |v: &Vec<u8>| u32::from_le_bytes([v[0], v[1], v[2], v[3]]);
+        let n = 1_000_000_000;
+        let end = extrapolate_by(start.clone(), step, key, n);
+        assert_eq!(end[0], (n % 201) as u8);
+        assert_eq!(find_cycle_by(start, step, key), (0, 201));
+    }
+}
@@ -0,0 +1,339 @@
+// Subsets are reported as indices into the input, in increasing order.
+
+/// How many subsets of `weights` sum to `target`, by size: `counts[k]` is the
+/// number with `k` items.
+pub fn count_subsets_by_size(weights: &[usize], target: usize) -> Vec<u64> {
+    let n = weights.len();
+    // counts[k][s]: subsets of the items so far with k items summing to s
+    let mut counts = vec![vec![0u64; target + 1]; n + 1];
+    counts[0][0] = 1;
+    for (i, &w) in weights.iter().enumerate().filter(|&(_, &w)| w <= target) {
+        for k in (0..=i).rev() {
+            for s in (0..=target - w).rev() {
+                counts[k + 1][s + w] += counts[k][s];
+            }
+        }
+    }
+    counts.into_iter().map(|by_sum| by_sum[target]).collect()
+}
+
+/// How many subsets of `weights` sum to `target`.
+pub fn count_subsets(weights: &[usize], target: usize) -> u64 {
+    let mut counts = vec![0u64; target + 1];
+    counts[0] = 1;
+    for &w in weights.iter().filter(|&&w| w <= target) {
+        for s in (w..=target).rev() {
+            counts[s] += counts[s - w];
+        }
+    }
+    counts[target]
+}
+
+/// Calls `f` with every subset of `weights` summing to `target`. A reachability
+/// table steers the search, so it never explores a branch without a solution.
+pub fn for_each_subset_with_sum(weights: &[usize], target: usize, mut f: impl FnMut(&[usize])) {
+    let n = weights.len();
+    // reach[i][s]: some subset of items i.. sums to s
+    let mut reach = vec![vec![false; target + 1]; n + 1];
+    reach[n][0] = true;
+    for i in (0..n).rev() {
+        for s in 0..=target {
+            reach[i][s] = reach[i + 1][s] || (s >= weights[i] && reach[i + 1][s - weights[i]]);
+        }
+    }
+
+    fn visit(
+        i: usize,
+        left: usize,
+        weights: &[usize],
+        reach: &[Vec<bool>],
+        chosen: &mut Vec<usize>,
+        f: &mut impl FnMut(&[usize]),
+    ) {
+        if i == weights.len() {
+            f(chosen);
+            return;
+        }
+        if left >= weights[i] && reach[i + 1][left - weights[i]] {
+            chosen.push(i);
+            visit(i + 1, left - weights[i], weights, reach, chosen, f);
+            chosen.pop();
+        }
+        if reach[i + 1][left] {
+            visit(i + 1, left, weights, reach, chosen, f);
+        }
+    }
+
+    if reach[0][target] {
+        visit(0, target, weights, &reach, &mut Vec::new(), &mut f);
+    }
+}
+
+/// A subset summing to `target` with as few items as possible.
+pub fn min_subset(weights: &[usize], target: usize) -> Option<Vec<usize>> {
+    const NONE: u32 = u32::MAX;
+    let n = weights.len();
+    // fewest[i][s]: fewest of the first i items summing to s
+    let mut fewest = vec![vec![NONE; target + 1]; n + 1];
+    fewest[0][0] = 0;
+    for (i, &w) in weights.iter().enumerate() {
+        for s in 0..=target {
+            let skip = fewest[i][s];
+            let take = if s >= w && fewest[i][s - w] != NONE {
+                fewest[i][s - w] + 1
+            } else {
+                NONE
+            };
+            fewest[i + 1][s] = skip.min(take);
+        }
+    }
+    if fewest[n][target] == NONE {
+        return None;
+    }
+
+    let mut subset = Vec::new();
+    let mut s = target;
+    for i in (0..n).rev() {
+        if fewest[i + 1][s] != fewest[i][s] {
+            subset.push(i);
+            s -= weights[i];
+        }
+    }
+    subset.reverse();
+    Some(subset)
+}
+
+/// 0/1 knapsack over `(weight, value)` items: the best total value within
+/// `capacity`, and the items giving it.
+pub fn knapsack(items: &[(usize, u64)], capacity: usize) -> (u64, Vec<usize>) {
+    let n = items.len();
+    // best[i][c]: best value from the first i items within capacity c
+    let mut best = vec![vec![0u64; capacity + 1]; n + 1];
+    for (i, &(w, v)) in items.iter().enumerate() {
+        for c in 0..=capacity {
+            best[i + 1][c] = best[i][c];
+            if c >= w {
+                best[i + 1][c] = best[i + 1][c].max(best[i][c - w] + v);
+            }
+        }
+    }
+
+    let mut chosen = Vec::new();
+    let mut c = capacity;
+    for i in (0..n).rev() {
+        if best[i + 1][c] != best[i][c] {
+            chosen.push(i);
+            c -= items[i].0;
+        }
+    }
+    chosen.reverse();
+    (best[n][capacity], chosen)
+}
+
+/// Splits `weights` into `k` groups of equal sum, returning the group of each
+/// item, or `None` if that's impossible.
+pub fn partition(weights: &[usize], k: usize) -> Option<Vec<usize>> {
+    assert!(k > 0, "Should split into at least one group");
+    let total: usize = weights.iter().sum();
+    if !total.is_multiple_of(k) {
+        return None;
+    }
+    let target = total / k;
+
+    // Placing heavy items first fails fast
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_unstable_by_key(|&i| std::cmp::Reverse(weights[i]));
+
+    fn place(
+        next: usize,
+        order: &[usize],
+        weights: &[usize],
+        target: usize,
+        sums: &mut [usize],
+        groups: &mut [usize],
+    ) -> bool {
+        let Some(&item) = order.get(next) else {
+            return true;
+        };
+        let w = weights[item];
+        for g in 0..sums.len() {
+            // Groups with the same sum so far are interchangeable, try only one
+            if sums[g] + w > target || sums[..g].contains(&sums[g]) {
+                continue;
+            }
+            sums[g] += w;
+            groups[item] = g;
+            if place(next + 1, order, weights, target, sums, groups) {
+                return true;
+            }
+            sums[g] -= w;
+        }
+        false
+    }
+
+    let mut groups = vec![0; weights.len()];
+    place(0, &order, weights, target, &mut vec![0; k], &mut groups).then_some(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinatorics::{bits, for_each_combination, subsets};
+    use crate::rng::Rng;
+
+    /// 2015 day 17 example.
+    const CONTAINERS: [usize; 5] = [20, 15, 10, 5, 5];
+
+    fn sum(weights: &[usize], mask: u64) -> usize {
+        bits(mask).map(|i| weights[i]).sum()
+    }
+
+    fn random_weights(rng: &mut Rng, n: usize, max: u64) -> Vec<usize> {
+        (0..n).map(|_| rng.below(max) as usize + 1).collect()
+    }
+
+    #[test]
+    fn test_containers() {
+        assert_eq!(count_subsets(&CONTAINERS, 25), 4);
+        assert_eq!(
+            count_subsets_by_size(&CONTAINERS, 25),
+            vec![0, 0, 3, 1, 0, 0]
+        );
+        assert_eq!(min_subset(&CONTAINERS, 25).unwrap().len(), 2);
+        assert_eq!(min_subset(&CONTAINERS, 26), None);
+
+        let mut found = Vec::new();
+        for_each_subset_with_sum(&CONTAINERS, 25, |s| found.push(s.to_vec()));
+        assert_eq!(
+            found,
+            vec![vec![0, 3], vec![0, 4], vec![1, 2], vec![1, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_subset_sums_against_brute_force() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        for n in 0..12 {
+            let weights = random_weights(&mut rng, n, 10);
+            for target in 0..30 {
+                let masks: Vec<u64> = subsets(n as u32)
+                    .filter(|&m| sum(&weights, m) == target)
+                    .collect();
+                assert_eq!(count_subsets(&weights, target), masks.len() as u64);
+                let by_size = count_subsets_by_size(&weights, target);
+                for (k, &count) in by_size.iter().enumerate() {
+                    assert_eq!(
+                        count,
+                        masks
+                            .iter()
+                            .filter(|m| m.count_ones() as usize == k)
+                            .count() as u64
+                    );
+                }
+
+                let fewest = masks.iter().map(|m| m.count_ones() as usize).min();
+                let witness = min_subset(&weights, target);
+                assert_eq!(witness.as_ref().map(Vec::len), fewest);
+                if let Some(witness) = witness {
+                    assert_eq!(witness.iter().map(|&i| weights[i]).sum::<usize>(), target);
+                }
+
+                let mut found = 0;
+                for_each_subset_with_sum(&weights, target, |s| {
+                    assert_eq!(s.iter().map(|&i| weights[i]).sum::<usize>(), target);
+                    found += 1;
+                });
+                assert_eq!(found, masks.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_knapsack() {
+        let items = [(12, 4), (2, 2), (1, 1), (1, 2), (4, 10)];
+        assert_eq!(knapsack(&items, 15), (15, vec![1, 2, 3, 4]));
+        assert_eq!(knapsack(&items, 0), (0, vec![]));
+
+        let mut rng = Rng::new(0x1234_5678_9abc_def1);
+        for n in 0..10 {
+            let weights = random_weights(&mut rng, n, 8);
+            let values = random_weights(&mut rng, n, 20);
+            let items: Vec<_> = weights
+                .iter()
+                .zip(&values)
+                .map(|(&w, &v)| (w, v as u64))
+                .collect();
+            for capacity in 0..20 {
+                let brute = subsets(n as u32)
+                    .filter(|&m| sum(&weights, m) <= capacity)
+                    .map(|m| sum(&values, m) as u64)
+                    .max()
+                    .unwrap();
+                let (best, chosen) = knapsack(&items, capacity);
+                assert_eq!(best, brute);
+                assert!(chosen.iter().map(|&i| weights[i]).sum::<usize>() <= capacity);
+                assert_eq!(chosen.iter().map(|&i| items[i].1).sum::<u64>(), best);
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition_against_brute_force() {
+        let mut rng = Rng::new(0x0bad_cafe_dead_beef);
+        for n in 0..9 {
+            let weights = random_weights(&mut rng, n, 6);
+            for k in 1..4usize {
+                // Try every assignment of items to groups
+                let brute = (0..k.pow(n as u32)).any(|code| {
+                    let mut sums = vec![0; k];
+                    let mut code = code;
+                    for &w in &weights {
+                        sums[code % k] += w;
+                        code /= k;
+                    }
+                    sums.iter().all(|&s| s == sums[0])
+                });
+                let groups = partition(&weights, k);
+                assert_eq!(groups.is_some(), brute, "{weights:?} into {k}");
+                if let Some(groups) = groups {
+                    let mut sums = vec![0; k];
+                    weights
+                        .iter()
+                        .zip(&groups)
+                        .for_each(|(&w, &g)| sums[g] += w);
+                    assert!(sums.iter().all(|&s| s == sums[0]));
+                }
+            }
+        }
+    }
+
+    /// 2015 day 24: the smallest first group, then the smallest product, such
+    /// that the rest still splits evenly.
+    fn entanglement(weights: &[usize], groups: usize) -> Option<usize> {
+        let target = weights.iter().sum::<usize>() / groups;
+        (1..=weights.len()).find_map(|k| {
+            let mut best = None;
+            for_each_combination(weights.len(), k, |c| {
+                if c.iter().map(|&i| weights[i]).sum::<usize>() != target {
+                    return;
+                }
+                let rest: Vec<_> = (0..weights.len())
+                    .filter(|i| !c.contains(i))
+                    .map(|i| weights[i])
+                    .collect();
+                if partition(&rest, groups - 1).is_some() {
+                    let product = c.iter().map(|&i| weights[i]).product::<usize>();
+                    best = Some(best.map_or(product, |b: usize| b.min(product)));
+                }
+            });
+            best
+        })
+    }
+
+    #[test]
+    fn test_packages() {
+        let weights = [1, 2, 3, 4, 5, 7, 8, 9, 10, 11];
+        assert_eq!(entanglement(&weights, 3), Some(99));
+        assert_eq!(entanglement(&weights, 4), Some(44));
+    }
+}
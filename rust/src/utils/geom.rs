@@ -0,0 +1,237 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+pub mod polygon;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(self, other: Self) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Distance when diagonal steps are allowed.
+    pub fn chebyshev(self, other: Self) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+}
+
+impl Point3 {
+    pub const ORIGIN: Self = Self::new(0, 0, 0);
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Self) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    /// Distance when diagonal steps are allowed.
+    pub fn chebyshev(self, other: Self) -> i64 {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+}
+
+macro_rules! impl_ops {
+    ($t:ident, $($c:ident),*) => {
+        impl Add for $t {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self { $($c: self.$c + rhs.$c),* }
+            }
+        }
+
+        impl Sub for $t {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($c: self.$c - rhs.$c),* }
+            }
+        }
+
+        impl Mul<i64> for $t {
+            type Output = Self;
+
+            fn mul(self, rhs: i64) -> Self {
+                Self { $($c: self.$c * rhs),* }
+            }
+        }
+
+        impl Neg for $t {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($c: -self.$c),* }
+            }
+        }
+
+        impl AddAssign for $t {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $t {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+impl_ops!(Point2, x, y);
+impl_ops!(Point3, x, y, z);
+
+/// A grid direction, in clockwise order. Up is towards negative `y`, like in
+/// [`Grid`](crate::grid::Grid).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn turn_around(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    pub fn unit(self) -> Point2 {
+        match self {
+            Direction::Up => Point2::new(0, -1),
+            Direction::Right => Point2::new(1, 0),
+            Direction::Down => Point2::new(0, 1),
+            Direction::Left => Point2::new(-1, 0),
+        }
+    }
+}
+
+/// Reads arrows (`^>v<`), letters (`URDL`) and compass points (`NESW`).
+impl TryFrom<u8> for Direction {
+    type Error = u8;
+
+    fn try_from(b: u8) -> Result<Self, u8> {
+        match b {
+            b'^' | b'U' | b'N' => Ok(Direction::Up),
+            b'>' | b'R' | b'E' => Ok(Direction::Right),
+            b'v' | b'D' | b'S' => Ok(Direction::Down),
+            b'<' | b'L' | b'W' => Ok(Direction::Left),
+            _ => Err(b),
+        }
+    }
+}
+
+impl Add<Direction> for Point2 {
+    type Output = Self;
+
+    fn add(self, rhs: Direction) -> Self {
+        self + rhs.unit()
+    }
+}
+
+impl AddAssign<Direction> for Point2 {
+    fn add_assign(&mut self, rhs: Direction) {
+        *self = *self + rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_point_arithmetic() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(4, 2);
+        assert_eq!(a + b, Point2::new(5, 0));
+        assert_eq!(b - a, Point2::new(3, 4));
+        assert_eq!(-a * 3, Point2::new(-3, 6));
+        assert_eq!(
+            Point3::new(1, 2, 3) + Point3::new(1, 1, 1),
+            Point3::new(2, 3, 4)
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(4, 2);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        let c = Point3::new(1, 1, 1);
+        assert_eq!(Point3::ORIGIN.manhattan(-c), 3);
+        assert_eq!(Point3::ORIGIN.chebyshev(c * 5), 5);
+    }
+
+    #[rstest]
+    #[case(b'^', Direction::Up)]
+    #[case(b'D', Direction::Down)]
+    #[case(b'E', Direction::Right)]
+    #[case(b'<', Direction::Left)]
+    fn test_parse_direction(#[case] input: u8, #[case] expected: Direction) {
+        assert_eq!(Direction::try_from(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_direction_error() {
+        assert_eq!(Direction::try_from(b'x'), Err(b'x'));
+    }
+
+    #[test]
+    fn test_turns() {
+        for d in Direction::ALL {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.turn_right().turn_right(), d.turn_around());
+            assert_eq!(d.turn_around().unit(), -d.unit());
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    }
+
+    #[test]
+    fn test_walk() {
+        let mut p = Point2::ORIGIN;
+        for b in b">>^" {
+            p += Direction::try_from(*b).unwrap();
+        }
+        assert_eq!(p, Point2::new(2, -1));
+    }
+}
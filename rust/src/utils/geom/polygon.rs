@@ -0,0 +1,243 @@
+use super::{Direction, Point2};
+use crate::math::gcd;
+
+/// A closed lattice polygon, edges running between consecutive vertices and
+/// from the last back to the first.
+///
+/// With y pointing down, as in [`Direction`], a positive signed area means the
+/// vertices run clockwise on screen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point2>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point2>) -> Self {
+        Self { vertices }
+    }
+
+    /// Traces `moves` from the origin. Returning to the origin at the end is
+    /// optional, the polygon closes itself either way.
+    pub fn from_moves(moves: impl IntoIterator<Item = (Direction, i64)>) -> Self {
+        let mut vertices = vec![Point2::ORIGIN];
+        for (dir, len) in moves {
+            vertices.push(*vertices.last().unwrap() + dir.unit() * len);
+        }
+        if vertices.len() > 1 && vertices.last() == vertices.first() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point2] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Twice the signed area by the shoelace formula, which is always whole.
+    pub fn double_signed_area(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128)
+            .sum()
+    }
+
+    /// Area enclosed. Panics if it isn't whole, which only polygons with
+    /// diagonal edges can do: use [`Self::double_signed_area`] for those.
+    pub fn area(&self) -> i128 {
+        let double = self.double_signed_area().abs();
+        assert!(
+            double % 2 == 0,
+            "Area should be whole, use double_signed_area"
+        );
+        double / 2
+    }
+
+    /// Euclidean length of the boundary.
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| ((a.x - b.x) as f64).hypot((a.y - b.y) as f64))
+            .sum()
+    }
+
+    /// Lattice points on the boundary. For axis-aligned edges this is also the
+    /// perimeter. Only exact for a simple polygon, since edges that overlap are
+    /// counted twice.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| gcd(a.x - b.x, a.y - b.y) as i128)
+            .sum()
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem: `A = I + B / 2 - 1`.
+    /// The theorem needs a simple polygon with nonzero area, so anything with
+    /// fewer than three vertices or zero area has no interior.
+    pub fn interior_points(&self) -> i128 {
+        let double = self.double_signed_area().abs();
+        if self.vertices.len() < 3 || double == 0 {
+            return 0;
+        }
+        (double - self.boundary_points()) / 2 + 1
+    }
+
+    /// Lattice points inside or on the boundary, like the cubes dug out in 2023
+    /// day 18. Only exact for a simple polygon, like [`Self::boundary_points`].
+    pub fn lattice_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn on_boundary(&self, p: Point2) -> bool {
+        self.edges().any(|(a, b)| {
+            cross(a, b, p) == 0
+                && a.x.min(b.x) <= p.x
+                && p.x <= a.x.max(b.x)
+                && a.y.min(b.y) <= p.y
+                && p.y <= a.y.max(b.y)
+        })
+    }
+
+    /// Whether `p` is inside or on the boundary.
+    pub fn contains(&self, p: Point2) -> bool {
+        if self.on_boundary(p) {
+            return true;
+        }
+        // Cast a ray towards +x and count the edges it crosses. Each edge
+        // counts its lower end but not its upper one, so vertices on the ray
+        // are counted once.
+        let crossings = self
+            .edges()
+            .filter(|&(a, b)| (a.y > p.y) != (b.y > p.y))
+            .filter(|&(a, b)| {
+                // p is left of the edge, signed by the edge's direction
+                let side = cross(a, b, p);
+                if b.y > a.y { side > 0 } else { side < 0 }
+            })
+            .count();
+        crossings % 2 == 1
+    }
+}
+
+/// Cross product of `b - a` and `p - a`.
+fn cross(a: Point2, b: Point2, p: Point2) -> i128 {
+    (b.x - a.x) as i128 * (p.y - a.y) as i128 - (b.y - a.y) as i128 * (p.x - a.x) as i128
+}
+
+impl FromIterator<Point2> for Polygon {
+    fn from_iter<I: IntoIterator<Item = Point2>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// 2023 day 18 example.
+    const DIG_PLAN: &str = "R 6\nD 5\nL 2\nD 2\nR 2\nD 2\nL 5\nU 2\nL 1\nU 2\nR 2\nU 3\nL 2\nU 2";
+
+    fn dig_plan() -> Polygon {
+        Polygon::from_moves(DIG_PLAN.lines().map(|line| {
+            let dir = Direction::try_from(line.as_bytes()[0]).unwrap();
+            (dir, line[2..].parse().unwrap())
+        }))
+    }
+
+    fn points(coords: &[(i64, i64)]) -> Polygon {
+        coords.iter().map(|&(x, y)| Point2::new(x, y)).collect()
+    }
+
+    /// Checks the lattice counts against `contains` on every point nearby.
+    fn check_against_points(polygon: &Polygon) {
+        let (min_x, max_x) = (
+            polygon.vertices().iter().map(|p| p.x).min().unwrap(),
+            polygon.vertices().iter().map(|p| p.x).max().unwrap(),
+        );
+        let (min_y, max_y) = (
+            polygon.vertices().iter().map(|p| p.y).min().unwrap(),
+            polygon.vertices().iter().map(|p| p.y).max().unwrap(),
+        );
+        let (mut inside, mut boundary) = (0, 0);
+        for y in min_y - 1..=max_y + 1 {
+            for x in min_x - 1..=max_x + 1 {
+                let p = Point2::new(x, y);
+                boundary += polygon.on_boundary(p) as i128;
+                inside += (polygon.contains(p) && !polygon.on_boundary(p)) as i128;
+            }
+        }
+        assert_eq!(boundary, polygon.boundary_points());
+        assert_eq!(inside, polygon.interior_points());
+    }
+
+    #[test]
+    fn test_dig_plan() {
+        let polygon = dig_plan();
+        assert_eq!(polygon.vertices().len(), 14);
+        assert_eq!(polygon.boundary_points(), 38);
+        assert_eq!(polygon.lattice_points(), 62);
+        assert_eq!(polygon.area(), 42);
+        // Right, then down on screen: clockwise
+        assert!(polygon.double_signed_area() > 0);
+        check_against_points(&polygon);
+    }
+
+    #[rstest]
+    #[case(&[(0, 0), (4, 0), (4, 4), (0, 4)], 32, 16, 9)]
+    #[case(&[(0, 4), (4, 4), (4, 0), (0, 0)], -32, 16, 9)]
+    #[case(&[(0, 0), (6, 0), (0, 4)], 24, 12, 7)]
+    #[case(&[(0, 0), (3, 1), (1, 2)], 5, 3, 2)]
+    #[case(&[(0, 0), (4, 0), (4, 4), (2, 1), (0, 4)], 20, 14, 4)]
+    fn test_pick(
+        #[case] coords: &[(i64, i64)],
+        #[case] double_area: i128,
+        #[case] boundary: i128,
+        #[case] interior: i128,
+    ) {
+        let polygon = points(coords);
+        assert_eq!(polygon.double_signed_area(), double_area);
+        assert_eq!(polygon.boundary_points(), boundary);
+        assert_eq!(polygon.interior_points(), interior);
+        check_against_points(&polygon);
+    }
+
+    #[rstest]
+    #[case(Polygon::default(), 0)]
+    #[case(Polygon::from_moves([(Direction::Right, 4), (Direction::Left, 4)]), 8)]
+    #[case(points(&[(0, 0), (2, 2), (4, 4)]), 8)]
+    fn test_degenerate(#[case] polygon: Polygon, #[case] boundary: i128) {
+        assert_eq!(polygon.double_signed_area(), 0);
+        assert_eq!(polygon.boundary_points(), boundary);
+        assert_eq!(polygon.interior_points(), 0);
+    }
+
+    #[test]
+    fn test_perimeter() {
+        assert_eq!(points(&[(0, 0), (3, 0), (3, 4)]).perimeter(), 12.0);
+        assert_eq!(dig_plan().perimeter(), 38.0);
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        // Twice the area alone overflows an i64
+        let n = 3_000_000_000;
+        let polygon = Polygon::from_moves([
+            (Direction::Right, n),
+            (Direction::Down, n),
+            (Direction::Left, n),
+            (Direction::Up, n),
+        ]);
+        assert_eq!(polygon.area(), (n as i128).pow(2));
+        assert_eq!(polygon.lattice_points(), (n as i128 + 1).pow(2));
+        assert!(polygon.contains(Point2::new(n / 2, n)));
+        assert!(!polygon.contains(Point2::new(n + 1, n / 2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Area should be whole")]
+    fn test_half_area() {
+        points(&[(0, 0), (1, 0), (0, 1)]).area();
+    }
+}
@@ -0,0 +1,481 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Edge weights. `Default` is the zero cost.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<C: Copy + Ord + Default + Add<Output = C>> Cost for C {}
+
+/// Result of a single-source search: the distance to every state reached and
+/// the state each was first reached from.
+#[derive(Clone, Debug)]
+pub struct Paths<S, C> {
+    pub dist: HashMap<S, C>,
+    pub prev: HashMap<S, S>,
+    /// The first goal state reached, if the search stopped at one.
+    pub goal: Option<S>,
+}
+
+impl<S: Clone + Hash + Eq, C: Copy> Paths<S, C> {
+    fn new(start: S, zero: C) -> Self {
+        Self {
+            dist: HashMap::from([(start, zero)]),
+            prev: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    pub fn distance(&self, state: &S) -> Option<C> {
+        self.dist.get(state).copied()
+    }
+
+    /// States from the start to `to`, both included.
+    pub fn path(&self, to: &S) -> Option<Vec<S>> {
+        self.dist.contains_key(to).then(|| {
+            let mut path: Vec<S> =
+                std::iter::successors(Some(to.clone()), |s| self.prev.get(s).cloned()).collect();
+            path.reverse();
+            path
+        })
+    }
+
+    /// Distance to and path towards [`Self::goal`].
+    pub fn goal_path(&self) -> Option<(C, Vec<S>)> {
+        let goal = self.goal.as_ref()?;
+        Some((self.dist[goal], self.path(goal)?))
+    }
+}
+
+/// Breadth-first search from `start`, stopping at the first state matching
+/// `goal`. Pass `|_| false` to explore everything reachable.
+pub fn bfs<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Paths<S, usize>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut paths = Paths::new(start.clone(), 0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            paths.goal = Some(state);
+            break;
+        }
+        let d = paths.dist[&state] + 1;
+        for next in neighbours(&state) {
+            if !paths.dist.contains_key(&next) {
+                paths.dist.insert(next.clone(), d);
+                paths.prev.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    paths
+}
+
+/// Dijkstra's algorithm over `(state, cost)` edges. Costs must not be negative.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Paths<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, neighbours, |_| C::default(), goal)
+}
+
+/// A* search. The heuristic must never overestimate the remaining cost to a
+/// goal, or the path found may not be the shortest.
+pub fn astar<S, C, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Paths<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let zero = C::default();
+    let mut paths = Paths::new(start.clone(), zero);
+    // The heap holds indices into `states`, so states need no ordering
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), zero, 0))]);
+    let mut states = vec![start];
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        let state = &states[i];
+        if cost > paths.dist[state] {
+            continue;
+        }
+        if goal(state) {
+            paths.goal = Some(state.clone());
+            break;
+        }
+        let state = state.clone();
+        for (next, step) in neighbours(&state) {
+            let d = cost + step;
+            if paths.dist.get(&next).is_none_or(|&old| d < old) {
+                paths.dist.insert(next.clone(), d);
+                paths.prev.insert(next.clone(), state.clone());
+                heap.push(Reverse((d + heuristic(&next), d, states.len())));
+                states.push(next);
+            }
+        }
+    }
+    paths
+}
+
+/// Every state reachable from `start`, including itself.
+pub fn reachable<S, I>(start: S, mut neighbours: impl FnMut(&S) -> I) -> HashSet<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = HashSet::from([start.clone()]);
+    let mut stack = vec![start];
+    while let Some(state) = stack.pop() {
+        for next in neighbours(&state) {
+            if seen.insert(next.clone()) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Result of [`all_shortest_paths`]: every predecessor on some shortest path,
+/// and how many shortest paths reach each state.
+#[derive(Clone, Debug)]
+pub struct AllPaths<S, C> {
+    pub dist: HashMap<S, C>,
+    pub prev: HashMap<S, Vec<S>>,
+    pub counts: HashMap<S, u64>,
+}
+
+impl<S: Clone + Hash + Eq, C: Copy> AllPaths<S, C> {
+    pub fn distance(&self, state: &S) -> Option<C> {
+        self.dist.get(state).copied()
+    }
+
+    /// Number of distinct shortest paths from the start to `to`.
+    pub fn count(&self, to: &S) -> u64 {
+        self.counts.get(to).copied().unwrap_or(0)
+    }
+
+    /// States lying on at least one shortest path to any of `targets`.
+    pub fn on_paths<'a>(&self, targets: impl IntoIterator<Item = &'a S>) -> HashSet<S>
+    where
+        S: 'a,
+    {
+        let mut seen = HashSet::new();
+        let mut stack: Vec<S> = targets
+            .into_iter()
+            .filter(|s| self.dist.contains_key(s))
+            .cloned()
+            .collect();
+        while let Some(state) = stack.pop() {
+            if seen.insert(state.clone()) {
+                stack.extend(self.prev.get(&state).into_iter().flatten().cloned());
+            }
+        }
+        seen
+    }
+}
+
+/// Dijkstra's algorithm keeping every shortest path. Costs must be positive, so
+/// a state's count is final by the time it leaves the heap.
+pub fn all_shortest_paths<S, C, I>(start: S, mut neighbours: impl FnMut(&S) -> I) -> AllPaths<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let zero = C::default();
+    let mut paths = AllPaths {
+        dist: HashMap::from([(start.clone(), zero)]),
+        prev: HashMap::new(),
+        counts: HashMap::from([(start.clone(), 1)]),
+    };
+    let mut heap = BinaryHeap::from([Reverse((zero, 0))]);
+    let mut states = vec![start];
+    while let Some(Reverse((cost, i))) = heap.pop() {
+        let state = states[i].clone();
+        if cost > paths.dist[&state] {
+            continue;
+        }
+        let count = paths.counts[&state];
+        for (next, step) in neighbours(&state) {
+            let d = cost + step;
+            match paths.dist.get(&next) {
+                Some(&old) if d > old => continue,
+                Some(&old) if d == old => {
+                    paths.prev.get_mut(&next).unwrap().push(state.clone());
+                    *paths.counts.get_mut(&next).unwrap() += count;
+                }
+                _ => {
+                    paths.dist.insert(next.clone(), d);
+                    paths.prev.insert(next.clone(), vec![state.clone()]);
+                    paths.counts.insert(next.clone(), count);
+                    heap.push(Reverse((d, states.len())));
+                    states.push(next);
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// Orders the states reachable from `roots` so each comes after everything it
+/// depends on. On a dependency cycle, returns its states instead, each
+/// depending on the next and the last on the first.
+pub fn topo_sort<S, I>(
+    roots: impl IntoIterator<Item = S>,
+    deps: impl FnMut(&S) -> I,
+) -> Result<Vec<S>, Vec<S>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    topo_order(roots, deps, |_| false)
+}
+
+/// Like [`topo_sort`], but leaves out `known` states and anything only they
+/// depend on. Lets callers keep their own memo, such as a `Vec` indexed by id,
+/// and compute just what is missing in the returned order.
+pub fn topo_order<S, I>(
+    roots: impl IntoIterator<Item = S>,
+    mut deps: impl FnMut(&S) -> I,
+    mut known: impl FnMut(&S) -> bool,
+) -> Result<Vec<S>, Vec<S>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    // Position of each state on the current path
+    let mut on_path = HashMap::new();
+    let mut path: Vec<(S, I::IntoIter)> = Vec::new();
+    for root in roots {
+        let mut next = Some(root);
+        loop {
+            if let Some(state) = next.take()
+                && !done.contains(&state)
+                && !known(&state)
+            {
+                if let Some(&i) = on_path.get(&state) {
+                    return Err(path.drain(i..).map(|(s, _)| s).collect());
+                }
+                on_path.insert(state.clone(), path.len());
+                let pending = deps(&state).into_iter();
+                path.push((state, pending));
+            }
+            let Some((_, pending)) = path.last_mut() else {
+                break;
+            };
+            next = pending.next();
+            if next.is_none() {
+                let (state, _) = path.pop().unwrap();
+                on_path.remove(&state);
+                done.insert(state.clone());
+                order.push(state);
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Computes the value of every state reachable from `roots`, dependencies
+/// first, without recursing. `eval` sees the values of everything its state
+/// depends on. States already in `values` are kept as they are and their
+/// dependencies aren't visited. On a dependency cycle, returns it as
+/// [`topo_sort`] does and leaves `values` untouched.
+pub fn evaluate<S, V, I>(
+    roots: impl IntoIterator<Item = S>,
+    deps: impl FnMut(&S) -> I,
+    mut eval: impl FnMut(&S, &HashMap<S, V>) -> V,
+    values: &mut HashMap<S, V>,
+) -> Result<(), Vec<S>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    for state in topo_order(roots, deps, |s| values.contains_key(s))? {
+        let value = eval(&state, values);
+        values.insert(state, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Point2;
+    use crate::grid::Grid;
+    use rstest::rstest;
+
+    const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E";
+
+    fn maze() -> (Grid<bool>, Point2, Point2) {
+        let grid = Grid::parse(MAZE, |c| c != b'#');
+        let start = Grid::parse(MAZE, |c| c).find(|&c| c == b'S').unwrap();
+        let end = Grid::parse(MAZE, |c| c).find(|&c| c == b'E').unwrap();
+        (grid, start, end)
+    }
+
+    #[test]
+    fn test_bfs_maze() {
+        let (grid, start, end) = maze();
+        let open = |p: &Point2| grid.neighbors4(*p).filter(|&q| grid[q]).collect::<Vec<_>>();
+        let paths = bfs(start, open, |&p| p == end);
+        let (d, path) = paths.goal_path().unwrap();
+        assert_eq!(d, 15);
+        assert_eq!(path.len(), 16);
+        assert_eq!((path[0], path[15]), (start, end));
+        assert!(
+            path.windows(2)
+                .all(|w| (w[0] - w[1]).manhattan(Point2::ORIGIN) == 1)
+        );
+    }
+
+    #[test]
+    fn test_astar_matches_bfs() {
+        let (grid, start, _) = maze();
+        let open = |p: &Point2| grid.neighbors4(*p).filter(|&q| grid[q]).collect::<Vec<_>>();
+        let all = bfs(start, open, |_| false);
+        for (target, &d) in &all.dist {
+            let weighted = |p: &Point2| open(p).into_iter().map(|q| (q, 1));
+            let paths = astar(start, weighted, |p| p.manhattan(*target), |p| p == target);
+            assert_eq!(paths.goal_path().unwrap().0, d as i64);
+        }
+        assert_eq!(all.dist.len(), reachable(start, open).len());
+        assert_eq!(
+            reachable(start, open).len(),
+            grid.iter().filter(|&&b| b).count()
+        );
+    }
+
+    #[test]
+    fn test_implicit_bfs() {
+        // Shortest way from 1 to 100 with x + 1 and x * 2
+        let paths = bfs(
+            1u32,
+            |&x| [x + 1, x * 2].into_iter().filter(|&y| y <= 100),
+            |&x| x == 100,
+        );
+        let (d, path) = paths.goal_path().unwrap();
+        assert_eq!(d, 8);
+        assert_eq!(path, vec![1, 2, 3, 6, 12, 24, 25, 50, 100]);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let edges: &[&[(usize, u32)]] = &[
+            &[(1, 7), (2, 9), (5, 14)],
+            &[(0, 7), (2, 10), (3, 15)],
+            &[(0, 9), (1, 10), (3, 11), (5, 2)],
+            &[(1, 15), (2, 11), (4, 6)],
+            &[(3, 6), (5, 9)],
+            &[(0, 14), (2, 2), (4, 9)],
+        ];
+        let paths = dijkstra(0, |&i| edges[i].iter().copied(), |_| false);
+        let dist: Vec<_> = (0..6).map(|i| paths.distance(&i).unwrap()).collect();
+        assert_eq!(dist, vec![0, 7, 9, 20, 20, 11]);
+        assert_eq!(paths.path(&4), Some(vec![0, 2, 5, 4]));
+        assert_eq!(paths.path(&6), None);
+    }
+
+    #[rstest]
+    #[case(1, 1, 2)]
+    #[case(2, 3, 10)]
+    #[case(5, 5, 252)]
+    fn test_count_lattice_paths(#[case] w: i64, #[case] h: i64, #[case] expected: u64) {
+        let grid = Grid::new(w as usize + 1, h as usize + 1, ());
+        let paths = all_shortest_paths(Point2::ORIGIN, |&p| grid.neighbors4(p).map(|q| (q, 1)));
+        let corner = Point2::new(w, h);
+        assert_eq!(paths.count(&corner), expected);
+        assert_eq!(paths.distance(&corner), Some(w + h));
+        // Every cell lies on some monotone path to the far corner
+        assert_eq!(paths.on_paths([&corner]).len(), grid.iter().count());
+    }
+
+    #[test]
+    fn test_topo_sort() {
+        let deps: &[&[usize]] = &[&[1, 2], &[3], &[3], &[], &[0]];
+        let order = topo_sort([4, 0], |&i| deps[i].iter().copied()).unwrap();
+        assert_eq!(order.len(), 5);
+        let position = |i| order.iter().position(|&j| j == i).unwrap();
+        assert!((0..5).all(|i| deps[i].iter().all(|&d| position(d) < position(i))));
+    }
+
+    #[rstest]
+    #[case(vec![vec![1], vec![2], vec![0]], vec![0, 1, 2])]
+    #[case(vec![vec![1], vec![2], vec![3], vec![1]], vec![1, 2, 3])]
+    #[case(vec![vec![1, 2], vec![], vec![2]], vec![2])]
+    fn test_topo_cycle(#[case] deps: Vec<Vec<usize>>, #[case] cycle: Vec<usize>) {
+        assert_eq!(topo_sort([0], |&i| deps[i].iter().copied()), Err(cycle));
+    }
+
+    #[test]
+    fn test_topo_order_with_memo() {
+        // Fibonacci with a Vec memo, where 5 is already known
+        let mut memo: Vec<Option<u64>> = vec![None; 9];
+        memo[5] = Some(5);
+        let deps = |&i: &usize| if i < 2 { vec![] } else { vec![i - 1, i - 2] };
+        let order = topo_order([8], deps, |&i| memo[i].is_some()).unwrap();
+        assert_eq!(order, vec![1, 0, 2, 3, 4, 6, 7, 8]);
+        for i in order {
+            memo[i] = Some(if i < 2 {
+                i as u64
+            } else {
+                memo[i - 1].unwrap() + memo[i - 2].unwrap()
+            });
+        }
+        assert_eq!(memo[8], Some(21));
+    }
+
+    #[test]
+    fn test_evaluate_long_chain() {
+        // Deep enough to overflow the stack if it recursed
+        let n = 200_000u64;
+        let mut values = HashMap::new();
+        let deps = |&i: &u64| (i > 0).then(|| i - 1);
+        evaluate(
+            [n],
+            deps,
+            |&i, v| if i == 0 { 0 } else { v[&(i - 1)] + i },
+            &mut values,
+        )
+        .unwrap();
+        assert_eq!(values[&n], n * (n + 1) / 2);
+
+        // Known values override and cut off their dependencies
+        let mut values = HashMap::from([(10, 1000)]);
+        evaluate([12], deps, |&i, v| v[&(i - 1)] + i, &mut values).unwrap();
+        assert_eq!(values[&12], 1000 + 11 + 12);
+        assert_eq!(values.len(), 3);
+    }
+
+    #[test]
+    fn test_count_weighted() {
+        // Three routes of cost 4 and one of cost 5 from 0 to 3
+        let edges: &[&[(u8, u8)]] = &[&[(1, 1), (2, 3), (3, 5)], &[(2, 2), (3, 3)], &[(3, 1)], &[]];
+        let paths = all_shortest_paths(0, |&i| edges[i as usize].iter().copied());
+        assert_eq!(paths.count(&3), 3);
+        assert_eq!(paths.distance(&3), Some(4));
+        assert_eq!(paths.on_paths([&3]), HashSet::from([0, 1, 2, 3]));
+    }
+}
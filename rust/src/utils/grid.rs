@@ -0,0 +1,778 @@
+use std::collections::HashMap;
+use std::ops::{Bound, Index, IndexMut, Range, RangeBounds};
+
+use crate::geom::Point2;
+use crate::parse::lines;
+
+const NEIGHBORS_4: [Point2; 4] = [
+    Point2::new(0, -1),
+    Point2::new(1, 0),
+    Point2::new(0, 1),
+    Point2::new(-1, 0),
+];
+
+const NEIGHBORS_8: [Point2; 8] = [
+    Point2::new(-1, -1),
+    Point2::new(0, -1),
+    Point2::new(1, -1),
+    Point2::new(1, 0),
+    Point2::new(1, 1),
+    Point2::new(0, 1),
+    Point2::new(-1, 1),
+    Point2::new(-1, 0),
+];
+
+/// A dense, row-major 2D grid. `y` grows downwards, as in the puzzle maps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        assert!(width > 0, "Grid should be at least one column wide");
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// `cells` in row-major order, `width` per row.
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "Cells should fill whole rows"
+        );
+        Self {
+            width,
+            height: cells.len() / width,
+            cells,
+        }
+    }
+
+    /// Builds a grid from a character map, one row per line. Blank lines around
+    /// the map are skipped, spaces are kept as cells.
+    pub fn parse<S: AsRef<[u8]> + ?Sized>(text: &S, mut cell: impl FnMut(u8) -> T) -> Self {
+        let is_newline = |b: &u8| matches!(b, b'\n' | b'\r');
+        let text = text.as_ref();
+        let start = text
+            .iter()
+            .position(|b| !is_newline(b))
+            .unwrap_or(text.len());
+        let end = text
+            .iter()
+            .rposition(|b| !is_newline(b))
+            .map_or(start, |i| i + 1);
+
+        let mut width = 0;
+        let mut cells = Vec::new();
+        for line in lines(&text[start..end]) {
+            if width == 0 {
+                width = line.len();
+            }
+            assert_eq!(line.len(), width, "All rows should have the same width");
+            cells.extend(line.iter().map(|&b| cell(b)));
+        }
+        Self::from_vec(width, cells)
+    }
+
+    /// [`Grid::parse`] for cell types that know their own characters.
+    pub fn parse_cells<S: AsRef<[u8]> + ?Sized>(text: &S) -> Result<Self, T::Error>
+    where
+        T: TryFrom<u8>,
+    {
+        let mut error = None;
+        let grid = Grid::parse(text, |b| match T::try_from(b) {
+            Ok(cell) => Some(cell),
+            Err(e) => {
+                error.get_or_insert(e);
+                None
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(grid.map(|cell| cell.unwrap())),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        (0..self.width as i64).contains(&p.x) && (0..self.height as i64).contains(&p.y)
+    }
+
+    fn index_of(&self, p: Point2) -> Option<usize> {
+        self.contains(p)
+            .then(|| p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: Point2) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point2) -> Option<&mut T> {
+        self.index_of(p).map(|i| &mut self.cells[i])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks_exact(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {x} out of bounds");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn column_mut(&mut self, x: usize) -> impl Iterator<Item = &mut T> {
+        assert!(x < self.width, "Column {x} out of bounds");
+        self.cells.iter_mut().skip(x).step_by(self.width)
+    }
+
+    /// All cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// All points with their cells, in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = (Point2, &T)> {
+        let width = self.width;
+        self.cells.iter().enumerate().map(move |(i, cell)| {
+            let p = Point2::new((i % width) as i64, (i / width) as i64);
+            (p, cell)
+        })
+    }
+
+    /// First point, in row-major order, whose cell satisfies `pred`.
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Point2> {
+        self.points().find(|(_, cell)| pred(cell)).map(|(p, _)| p)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.into_iter().map(f).collect(),
+        }
+    }
+
+    /// The up to 4 orthogonal neighbours of `p` inside the grid.
+    pub fn neighbors4(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+        NEIGHBORS_4
+            .iter()
+            .map(move |&d| p + d)
+            .filter(|&n| self.contains(n))
+    }
+
+    /// The up to 8 neighbours of `p`, diagonals included, inside the grid.
+    pub fn neighbors8(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+        NEIGHBORS_8
+            .iter()
+            .map(move |&d| p + d)
+            .filter(|&n| self.contains(n))
+    }
+
+    /// A mutable view of the cells in columns `xs` of rows `ys`.
+    pub fn view_mut(
+        &mut self,
+        xs: impl RangeBounds<usize>,
+        ys: impl RangeBounds<usize>,
+    ) -> ViewMut<'_, T> {
+        let xs = to_range(xs, self.width);
+        let ys = to_range(ys, self.height);
+        ViewMut { grid: self, xs, ys }
+    }
+}
+
+fn to_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "Range {start}..{end} out of bounds"
+    );
+    start..end
+}
+
+/// A rectangle of a [`Grid`], from [`Grid::view_mut`].
+pub struct ViewMut<'a, T> {
+    grid: &'a mut Grid<T>,
+    xs: Range<usize>,
+    ys: Range<usize>,
+}
+
+impl<T> ViewMut<'_, T> {
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let xs = self.xs.clone();
+        self.grid
+            .cells
+            .chunks_exact_mut(self.grid.width)
+            .skip(self.ys.start)
+            .take(self.ys.len())
+            .map(move |row| &mut row[xs.clone()])
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rows_mut().flatten()
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.rows_mut().for_each(|row| row.fill(value.clone()));
+    }
+
+    /// Replaces every cell with `f` of its old value.
+    pub fn map_in_place(&mut self, mut f: impl FnMut(&T) -> T) {
+        self.iter_mut().for_each(|cell| *cell = f(cell));
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(x < self.width, "Column {x} out of bounds");
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(x < self.width, "Column {x} out of bounds");
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point2) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("Point {p:?} out of bounds"))
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, p: Point2) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("Point {p:?} out of bounds"))
+    }
+}
+
+/// An unbounded grid storing only the cells that were set, with the bounding
+/// box of everything inserted so far.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point2, T>,
+    // Inclusive corners, only meaningful when not empty
+    min: Point2,
+    max: Point2,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            min: Point2::ORIGIN,
+            max: Point2::ORIGIN,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cell at `p`, returning its previous value.
+    pub fn insert(&mut self, p: Point2, value: T) -> Option<T> {
+        if self.cells.is_empty() {
+            (self.min, self.max) = (p, p);
+        } else {
+            self.min = Point2::new(self.min.x.min(p.x), self.min.y.min(p.y));
+            self.max = Point2::new(self.max.x.max(p.x), self.max.y.max(p.y));
+        }
+        self.cells.insert(p, value)
+    }
+
+    pub fn get(&self, p: Point2) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point2) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Inclusive top-left and bottom-right corners of the set cells.
+    pub fn bounds(&self) -> Option<(Point2, Point2)> {
+        (!self.is_empty()).then_some((self.min, self.max))
+    }
+
+    /// Set cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        let mut cells: Vec<(Point2, &T)> = self.cells.iter().map(|(&p, v)| (p, v)).collect();
+        cells.sort_unstable_by_key(|(p, _)| (p.y, p.x));
+        cells.into_iter()
+    }
+
+    /// Draws the bounding box one row per line, `cell` getting `None` for unset cells.
+    pub fn render(&self, mut cell: impl FnMut(Option<&T>) -> char) -> String {
+        let Some((min, max)) = self.bounds() else {
+            return String::new();
+        };
+
+        let mut out = String::new();
+        for y in min.y..=max.y {
+            if y > min.y {
+                out.push('\n');
+            }
+            out.extend((min.x..=max.x).map(|x| cell(self.get(Point2::new(x, y)))));
+        }
+        out
+    }
+}
+
+impl<T> Extend<(Point2, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Point2, T)>>(&mut self, iter: I) {
+        for (p, value) in iter {
+            self.insert(p, value);
+        }
+    }
+}
+
+impl<T> FromIterator<(Point2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point2, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+/// A dense grid of booleans, each row packed into `u64` words with `x` in bit
+/// `x % 64` of word `x / 64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// An all-clear grid.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0, "Grid should be at least one column wide");
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    /// Builds a grid from a character map, one row per line.
+    pub fn parse<S: AsRef<[u8]> + ?Sized>(text: &S, mut is_set: impl FnMut(u8) -> bool) -> Self {
+        let cells = Grid::parse(text, &mut is_set);
+        let mut grid = Self::new(cells.width(), cells.height());
+        for (p, &set) in cells.points() {
+            grid.set(p.x as usize, p.y as usize, set);
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        assert!(x < self.width, "Column {x} out of bounds");
+        self.words[y * self.words_per_row + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        assert!(x < self.width, "Column {x} out of bounds");
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+        let bit = 1 << (x % 64);
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    /// Applies `op(word, mask)` to every word touching columns `xs` of rows `ys`,
+    /// `mask` selecting the bits inside `xs`.
+    fn apply(
+        &mut self,
+        xs: impl RangeBounds<usize>,
+        ys: impl RangeBounds<usize>,
+        op: impl Fn(u64, u64) -> u64,
+    ) {
+        let xs = to_range(xs, self.width);
+        let ys = to_range(ys, self.height);
+        if xs.is_empty() {
+            return;
+        }
+
+        let (first, last) = (xs.start / 64, (xs.end - 1) / 64);
+        let first_mask = u64::MAX << (xs.start % 64);
+        let last_mask = u64::MAX >> (63 - (xs.end - 1) % 64);
+        for row in self
+            .words
+            .chunks_exact_mut(self.words_per_row)
+            .skip(ys.start)
+            .take(ys.len())
+        {
+            match &mut row[first..=last] {
+                [only] => *only = op(*only, first_mask & last_mask),
+                [head, middle @ .., tail] => {
+                    *head = op(*head, first_mask);
+                    middle
+                        .iter_mut()
+                        .for_each(|word| *word = op(*word, u64::MAX));
+                    *tail = op(*tail, last_mask);
+                }
+                [] => unreachable!(),
+            }
+        }
+    }
+
+    pub fn set_rect(&mut self, xs: impl RangeBounds<usize>, ys: impl RangeBounds<usize>) {
+        self.apply(xs, ys, |word, mask| word | mask);
+    }
+
+    pub fn clear_rect(&mut self, xs: impl RangeBounds<usize>, ys: impl RangeBounds<usize>) {
+        self.apply(xs, ys, |word, mask| word & !mask);
+    }
+
+    pub fn toggle_rect(&mut self, xs: impl RangeBounds<usize>, ys: impl RangeBounds<usize>) {
+        self.apply(xs, ys, |word, mask| word ^ mask);
+    }
+
+    /// Number of set cells.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// One generation of a Life-like automaton: a clear cell with a neighbour
+    /// count in `born` gets set, a set cell stays set with a count in `survive`.
+    /// Cells outside the grid count as clear. Conway's rules are `(&[3], &[2, 3])`.
+    ///
+    /// Panics on a rule count over 8, which no cell can reach.
+    pub fn life_step(&self, born: &[u8], survive: &[u8]) -> Self {
+        assert!(
+            born.iter().chain(survive).all(|&n| n <= 8),
+            "Neighbour counts should be at most 8"
+        );
+        let mut next = Self::new(self.width, self.height);
+        let row = |y: usize| &self.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+        let empty = vec![0; self.words_per_row];
+        // Valid bits of the last word in a row
+        let tail_mask = u64::MAX >> ((64 - self.width % 64) % 64);
+
+        for y in 0..self.height {
+            let above = if y > 0 { row(y - 1) } else { &empty };
+            let below = if y + 1 < self.height {
+                row(y + 1)
+            } else {
+                &empty
+            };
+            let here = row(y);
+
+            for w in 0..self.words_per_row {
+                // Counts 0..=8 as four bit planes, one cell per bit position
+                let mut count = [0u64; 4];
+                for (dy, line) in [above, here, below].into_iter().enumerate() {
+                    let prev = if w > 0 { line[w - 1] } else { 0 };
+                    let next = line.get(w + 1).copied().unwrap_or(0);
+                    let left = line[w] << 1 | prev >> 63;
+                    let right = line[w] >> 1 | next << 63;
+                    add_plane(&mut count, left);
+                    add_plane(&mut count, right);
+                    if dy != 1 {
+                        add_plane(&mut count, line[w]);
+                    }
+                }
+
+                let matching = |counts: &[u8]| {
+                    counts
+                        .iter()
+                        .map(|&n| {
+                            count
+                                .iter()
+                                .enumerate()
+                                .fold(u64::MAX, |acc, (bit, &plane)| {
+                                    acc & if n >> bit & 1 == 1 { plane } else { !plane }
+                                })
+                        })
+                        .fold(0, |acc, m| acc | m)
+                };
+
+                let cell = here[w];
+                let mut word = (!cell & matching(born)) | (cell & matching(survive));
+                if w + 1 == self.words_per_row {
+                    word &= tail_mask;
+                }
+                next.words[y * self.words_per_row + w] = word;
+            }
+        }
+        next
+    }
+}
+
+/// Adds the one-bit `bit` into the bit-sliced counters `count`.
+#[inline(always)]
+fn add_plane(count: &mut [u64; 4], mut bit: u64) {
+    for plane in count {
+        let sum = *plane ^ bit;
+        bit &= *plane;
+        *plane = sum;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Tile {
+        Open,
+        Wall,
+        Start,
+    }
+
+    impl TryFrom<u8> for Tile {
+        type Error = u8;
+
+        fn try_from(b: u8) -> Result<Self, u8> {
+            match b {
+                b'.' => Ok(Tile::Open),
+                b'#' => Ok(Tile::Wall),
+                b'S' => Ok(Tile::Start),
+                _ => Err(b),
+            }
+        }
+    }
+
+    const MAP: &str = "
+#####
+#S..#
+#.#.#
+#####
+";
+
+    #[test]
+    fn test_parse_cells() {
+        let grid: Grid<Tile> = Grid::parse_cells(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 4));
+        assert_eq!(grid[(2, 2)], Tile::Wall);
+        assert_eq!(grid[Point2::new(3, 1)], Tile::Open);
+        assert_eq!(grid.find(|&t| t == Tile::Start), Some(Point2::new(1, 1)));
+        assert_eq!(Grid::<Tile>::parse_cells("#.\n.x"), Err(b'x'));
+    }
+
+    #[test]
+    fn test_parse_keeps_spaces() {
+        let grid = Grid::parse("\n  #\n # \n#  \n\n", |b| b == b'#');
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert!(grid[(2, 0)] && grid[(1, 1)] && grid[(0, 2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one column")]
+    fn test_zero_width_panics() {
+        Grid::new(0, 3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "same width")]
+    fn test_parse_ragged_panics() {
+        Grid::parse("..\n...", |b| b);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let mut grid = Grid::from_vec(3, (0..6).collect());
+        assert_eq!(grid.row(1), [3, 4, 5]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![2, 5]);
+        grid.column_mut(0).for_each(|v| *v *= 10);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![[0, 1, 2], [30, 4, 5]]);
+    }
+
+    #[rstest]
+    #[case(Point2::new(0, 0), 2, 3)]
+    #[case(Point2::new(1, 1), 4, 8)]
+    #[case(Point2::new(2, 1), 3, 5)]
+    #[case::outside(Point2::new(-1, 0), 1, 2)]
+    fn test_neighbors(#[case] p: Point2, #[case] expected4: usize, #[case] expected8: usize) {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(grid.neighbors4(p).count(), expected4);
+        assert_eq!(grid.neighbors8(p).count(), expected8);
+    }
+
+    #[test]
+    fn test_get_out_of_bounds() {
+        let grid = Grid::new(2, 2, 0);
+        assert_eq!(grid.get(Point2::new(1, 1)), Some(&0));
+        assert_eq!(grid.get(Point2::new(2, 0)), None);
+        assert_eq!(grid.get(Point2::new(0, -1)), None);
+    }
+
+    #[test]
+    fn test_view_mut() {
+        let mut grid = Grid::new(4, 3, 0);
+        grid.view_mut(1..=2, ..2).fill(1);
+        grid.view_mut(2.., 1..).map_in_place(|v| v + 2);
+        let rows: Vec<&[i32]> = grid.rows().collect();
+        assert_eq!(rows, vec![[0, 1, 1, 0], [0, 1, 3, 2], [0, 0, 2, 2]]);
+    }
+
+    #[test]
+    fn test_sparse_bounds() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Point2::new(3, -1), 'a');
+        assert_eq!(
+            grid.bounds(),
+            Some((Point2::new(3, -1), Point2::new(3, -1)))
+        );
+        grid.insert(Point2::new(-2, 4), 'b');
+        assert_eq!(grid.insert(Point2::new(3, -1), 'c'), Some('a'));
+        assert_eq!(
+            grid.bounds(),
+            Some((Point2::new(-2, -1), Point2::new(3, 4)))
+        );
+        assert_eq!(grid.len(), 2);
+    }
+
+    #[test]
+    fn test_sparse_iter_row_major() {
+        let grid: SparseGrid<u8> = [(1, 1), (0, 1), (5, 0), (-1, 2)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| (Point2::new(x, y), i as u8))
+            .collect();
+        let order: Vec<u8> = grid.iter().map(|(_, &v)| v).collect();
+        assert_eq!(order, vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn test_sparse_render() {
+        let grid: SparseGrid<()> = [(0, 0), (2, 0), (1, 1), (-1, 2)]
+            .into_iter()
+            .map(|(x, y)| (Point2::new(x, y), ()))
+            .collect();
+        let drawn = grid.render(|cell| if cell.is_some() { '#' } else { '.' });
+        assert_eq!(drawn, ".#.#\n..#.\n#...");
+        assert_eq!(SparseGrid::<()>::new().render(|_| '#'), "");
+    }
+
+    #[test]
+    fn test_bit_rect_ops() {
+        let mut grid = BitGrid::new(130, 3);
+        grid.set_rect(60..=70, 0..2);
+        assert_eq!(grid.count_ones(), 22);
+        grid.toggle_rect(65..130, 1..);
+        assert_eq!(grid.count_ones(), 11 + (5 + 59) + 65);
+        grid.clear_rect(.., 2..);
+        assert_eq!(grid.count_ones(), 11 + (5 + 59));
+        assert!(grid.get(64, 1) && !grid.get(65, 1) && grid.get(129, 1));
+        assert!(!grid.get(59, 0) && grid.get(60, 0) && grid.get(70, 0) && !grid.get(71, 0));
+    }
+
+    #[test]
+    fn test_bit_rect_matches_grid() {
+        let mut bits = BitGrid::new(200, 5);
+        let mut cells = Grid::new(200, 5, false);
+        for (x1, x2) in [(0, 199), (3, 3), (63, 64), (100, 127), (128, 191), (5, 150)] {
+            bits.toggle_rect(x1..=x2, 1..4);
+            cells.view_mut(x1..=x2, 1..4).map_in_place(|b| !b);
+        }
+        for (p, &set) in cells.points() {
+            assert_eq!(bits.get(p.x as usize, p.y as usize), set, "{p:?}");
+        }
+        assert_eq!(bits.count_ones(), cells.iter().filter(|b| **b).count());
+    }
+
+    #[test]
+    fn test_life_step() {
+        // 2015 day 18 example
+        let mut grid = BitGrid::parse(".#.#.#\n...##.\n#....#\n..#...\n#.#..#\n####..", |b| {
+            b == b'#'
+        });
+        let counts: Vec<usize> = (0..4)
+            .map(|_| {
+                grid = grid.life_step(&[3], &[2, 3]);
+                grid.count_ones()
+            })
+            .collect();
+        assert_eq!(counts, vec![11, 8, 4, 4]);
+    }
+
+    #[test]
+    fn test_life_step_across_words() {
+        // A blinker straddling the word boundary, next to the right edge
+        let mut grid = BitGrid::new(66, 3);
+        grid.set_rect(63..=65, 1..=1);
+        let next = grid.life_step(&[3], &[2, 3]);
+        let set: Vec<(usize, usize)> = (0..3)
+            .flat_map(|y| (0..66).map(move |x| (x, y)))
+            .filter(|&(x, y)| next.get(x, y))
+            .collect();
+        assert_eq!(set, vec![(64, 0), (64, 1), (64, 2)]);
+        assert_eq!(next.life_step(&[3], &[2, 3]), grid);
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 8")]
+    fn test_life_step_rejects_large_counts() {
+        // 19 would otherwise match cells with 3 neighbours
+        BitGrid::new(3, 3).life_step(&[19], &[]);
+    }
+}
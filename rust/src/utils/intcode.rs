@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+
+use crate::parse::numbers;
+
+/// What the machine did on its last step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Running,
+    /// Blocked on an empty input queue. Stepping again retries the read.
+    Input,
+    /// Sent a value, also queued on `output`.
+    Output(i64),
+    Halted,
+}
+
+/// A 2019 Intcode machine.
+#[derive(Clone, Debug, Default)]
+pub struct Intcode {
+    memory: Vec<i64>,
+    ip: usize,
+    base: i64,
+    pub input: VecDeque<i64>,
+    pub output: VecDeque<i64>,
+    /// Value read from an empty input queue instead of blocking, as 2019 day 23
+    /// needs.
+    pub idle_input: Option<i64>,
+}
+
+/// A saved machine state, see [`Intcode::snapshot`].
+#[derive(Clone, Debug)]
+pub struct Snapshot(Intcode);
+
+impl Intcode {
+    pub fn new(program: &[i64]) -> Self {
+        Self {
+            memory: program.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn parse(text: &str) -> Self {
+        Self::new(&numbers(text).collect::<Vec<_>>())
+    }
+
+    pub fn with_input(mut self, input: impl IntoIterator<Item = i64>) -> Self {
+        self.input.extend(input);
+        self
+    }
+
+    /// Memory at `address`. Memory past the program reads as 0.
+    pub fn read(&self, address: usize) -> i64 {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    /// Writes to memory, growing it as needed.
+    pub fn write(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.clone())
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.clone_from(&snapshot.0);
+    }
+
+    /// Address of parameter `n` (from 1) of the current instruction.
+    fn address(&self, n: usize) -> usize {
+        let mode = self.read(self.ip) / [100, 1000, 10000][n - 1] % 10;
+        let raw = self.read(self.ip + n);
+        let address = match mode {
+            0 => raw,
+            1 => (self.ip + n) as i64,
+            2 => self.base + raw,
+            _ => panic!("Unknown parameter mode {mode} at {}", self.ip),
+        };
+        usize::try_from(address)
+            .unwrap_or_else(|_| panic!("Address {address} should not be negative"))
+    }
+
+    fn param(&self, n: usize) -> i64 {
+        self.read(self.address(n))
+    }
+
+    /// Executes one instruction.
+    pub fn step(&mut self) -> State {
+        let opcode = self.read(self.ip) % 100;
+        let (a, b) = (|| self.param(1), || self.param(2));
+        match opcode {
+            1 | 2 | 7 | 8 => {
+                let value = match opcode {
+                    1 => a() + b(),
+                    2 => a() * b(),
+                    7 => (a() < b()) as i64,
+                    _ => (a() == b()) as i64,
+                };
+                self.write(self.address(3), value);
+                self.ip += 4;
+            }
+            3 => {
+                let Some(value) = self.input.pop_front().or(self.idle_input) else {
+                    return State::Input;
+                };
+                self.write(self.address(1), value);
+                self.ip += 2;
+            }
+            4 => {
+                let value = a();
+                self.output.push_back(value);
+                self.ip += 2;
+                return State::Output(value);
+            }
+            5 | 6 => {
+                if (a() != 0) == (opcode == 5) {
+                    self.ip = usize::try_from(b()).expect("Jumps should not be negative");
+                } else {
+                    self.ip += 3;
+                }
+            }
+            9 => {
+                self.base += a();
+                self.ip += 2;
+            }
+            99 => return State::Halted,
+            _ => panic!("Unknown opcode {opcode} at {}", self.ip),
+        }
+        State::Running
+    }
+
+    /// Runs until the machine halts or needs input, queueing every output.
+    pub fn run(&mut self) -> State {
+        loop {
+            match self.step() {
+                State::Running | State::Output(_) => {}
+                state => return state,
+            }
+        }
+    }
+
+    /// Runs until there is an output to take, or the machine halts or needs
+    /// input first.
+    pub fn next_output(&mut self) -> State {
+        loop {
+            if let Some(value) = self.output.pop_front() {
+                return State::Output(value);
+            }
+            match self.step() {
+                State::Running | State::Output(_) => {}
+                state => return state,
+            }
+        }
+    }
+}
+
+/// Runs `machines` in order, each one's output feeding the next, and returns
+/// what the last one outputs.
+pub fn chain(machines: &mut [Intcode], input: impl IntoIterator<Item = i64>) -> Vec<i64> {
+    let mut values: Vec<i64> = input.into_iter().collect();
+    for machine in machines {
+        machine.input.extend(values);
+        machine.run();
+        values = machine.output.drain(..).collect();
+    }
+    values
+}
+
+/// Like [`chain`], but the last machine's output goes back to the first, until
+/// every machine halts or they all wait on each other. Returns everything the
+/// last machine sent.
+pub fn feedback(machines: &mut [Intcode], input: impl IntoIterator<Item = i64>) -> Vec<i64> {
+    let n = machines.len();
+    machines[0].input.extend(input);
+    let mut sent = Vec::new();
+    loop {
+        let mut progress = false;
+        let mut halted = true;
+        for i in 0..n {
+            halted &= machines[i].run() == State::Halted;
+            let values: Vec<_> = machines[i].output.drain(..).collect();
+            progress |= !values.is_empty();
+            if i == n - 1 {
+                sent.extend(&values);
+            }
+            machines[(i + 1) % n].input.extend(values);
+        }
+        if halted || !progress {
+            return sent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1,9,10,3,2,3,11,0,99,30,40,50", &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50])]
+    #[case("1,0,0,0,99", &[2, 0, 0, 0, 99])]
+    #[case("2,3,0,3,99", &[2, 3, 0, 6, 99])]
+    #[case("2,4,4,5,99,0", &[2, 4, 4, 5, 99, 9801])]
+    #[case("1,1,1,4,99,5,6,0,99", &[30, 1, 1, 4, 2, 5, 6, 0, 99])]
+    #[case("1002,4,3,4,33", &[1002, 4, 3, 4, 99])]
+    #[case("1101,100,-1,4,0", &[1101, 100, -1, 4, 99])]
+    fn test_memory(#[case] program: &str, #[case] expected: &[i64]) {
+        let mut machine = Intcode::parse(program);
+        assert_eq!(machine.run(), State::Halted);
+        assert_eq!(machine.memory(), expected);
+    }
+
+    const COMPARE: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+
+    #[rstest]
+    #[case("3,9,8,9,10,9,4,9,99,-1,8", 8, 1)]
+    #[case("3,9,8,9,10,9,4,9,99,-1,8", 7, 0)]
+    #[case("3,9,7,9,10,9,4,9,99,-1,8", 7, 1)]
+    #[case("3,3,1108,-1,8,3,4,3,99", 8, 1)]
+    #[case("3,3,1107,-1,8,3,4,3,99", 9, 0)]
+    #[case("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", 0, 0)]
+    #[case("3,3,1105,-1,9,1101,0,0,12,4,12,99,1", 5, 1)]
+    #[case(COMPARE, 7, 999)]
+    #[case(COMPARE, 8, 1000)]
+    #[case(COMPARE, 9, 1001)]
+    fn test_io(#[case] program: &str, #[case] input: i64, #[case] expected: i64) {
+        let mut machine = Intcode::parse(program).with_input([input]);
+        assert_eq!(machine.run(), State::Halted);
+        assert_eq!(machine.output, [expected]);
+    }
+
+    #[test]
+    fn test_relative_and_large() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut machine = Intcode::parse(quine);
+        machine.run();
+        assert_eq!(machine.output, numbers(quine).collect::<Vec<i64>>());
+
+        let mut machine = Intcode::parse("1102,34915192,34915192,7,4,7,99,0");
+        assert_eq!(machine.next_output(), State::Output(1219070632396864));
+        let mut machine = Intcode::parse("104,1125899906842624,99");
+        assert_eq!(machine.next_output(), State::Output(1125899906842624));
+        assert_eq!(machine.next_output(), State::Halted);
+    }
+
+    #[rstest]
+    #[case("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0", [4, 3, 2, 1, 0], 43210)]
+    #[case(
+        "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0",
+        [1, 0, 4, 3, 2],
+        65210
+    )]
+    fn test_chain(#[case] program: &str, #[case] phases: [i64; 5], #[case] expected: i64) {
+        let machine = Intcode::parse(program);
+        let mut amps = phases.map(|phase| machine.clone().with_input([phase]));
+        assert_eq!(chain(&mut amps, [0]), [expected]);
+    }
+
+    #[test]
+    fn test_feedback() {
+        let program =
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        let machine = Intcode::parse(program);
+        let mut amps = [9, 8, 7, 6, 5].map(|phase| machine.clone().with_input([phase]));
+        assert_eq!(feedback(&mut amps, [0]).last(), Some(&139629729));
+    }
+
+    #[test]
+    fn test_pause_and_snapshot() {
+        // Echoes inputs until it reads 0
+        let mut machine = Intcode::parse("3,20,4,20,1005,20,0,99");
+        assert_eq!(machine.run(), State::Input);
+        let saved = machine.snapshot();
+
+        machine.input.extend([5, 0]);
+        assert_eq!(machine.run(), State::Halted);
+        assert_eq!(machine.output, [5, 0]);
+
+        machine.restore(&saved);
+        assert!(machine.output.is_empty());
+        machine.input.push_back(7);
+        assert_eq!(machine.next_output(), State::Output(7));
+        assert_eq!(machine.next_output(), State::Input);
+
+        machine.idle_input = Some(0);
+        assert_eq!(machine.next_output(), State::Output(0));
+        assert_eq!(machine.run(), State::Halted);
+        assert_eq!(machine.read(1000), 0);
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+/// Integer types usable as dense ids.
+pub trait Id: Copy {
+    fn from_index(index: usize) -> Self;
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_id {
+    ($($t:ty),*) => {
+        $(
+            impl Id for $t {
+                #[inline(always)]
+                fn from_index(index: usize) -> Self {
+                    <$t>::try_from(index).expect("Too many identifiers for id type")
+                }
+
+                #[inline(always)]
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_id!(u8, u16, u32, usize);
+
+/// Maps borrowed identifiers to dense ids `0, 1, 2, ...` in order of first sight,
+/// so per-identifier data can live in a `Vec` instead of a string-keyed map.
+#[derive(Debug, Clone)]
+pub struct Interner<'a, I = u32> {
+    ids: HashMap<&'a str, I>,
+    names: Vec<&'a str>,
+}
+
+impl<I> Default for Interner<'_, I> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            names: Vec::new(),
+        }
+    }
+}
+
+impl<'a, I: Id> Interner<'a, I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `name`, assigning the next free one if it is new.
+    pub fn intern(&mut self, name: &'a str) -> I {
+        *self.ids.entry(name).or_insert_with(|| {
+            let id = I::from_index(self.names.len());
+            self.names.push(name);
+            id
+        })
+    }
+
+    /// Id of `name` if it was interned before.
+    pub fn get(&self, name: &str) -> Option<I> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: I) -> &'a str {
+        self.names[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// All names, indexed by id.
+    pub fn names(&self) -> &[&'a str] {
+        &self.names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_dense_and_stable() {
+        let mut interner: Interner<u16> = Interner::new();
+        assert_eq!(interner.intern("x"), 0);
+        assert_eq!(interner.intern("y"), 1);
+        assert_eq!(interner.intern("x"), 0);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.names(), ["x", "y"]);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let text = String::from("lx ly");
+        let mut interner: Interner = Interner::new();
+        let ids: Vec<u32> = text.split(' ').map(|w| interner.intern(w)).collect();
+        assert_eq!(interner.get("ly"), Some(ids[1]));
+        assert_eq!(interner.get("lz"), None);
+        assert_eq!(interner.name(ids[0]), "lx");
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many identifiers")]
+    fn test_id_overflow_panics() {
+        let names: Vec<String> = (0..=256).map(|i| i.to_string()).collect();
+        let mut interner: Interner<u8> = Interner::new();
+        names.iter().for_each(|name| {
+            interner.intern(name);
+        });
+    }
+}
@@ -0,0 +1,442 @@
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+/// Integer types usable as interval endpoints.
+pub trait Endpoint: Copy + Ord + Debug {
+    const MIN: Self;
+    const MAX: Self;
+    fn succ(self) -> Option<Self>;
+    fn pred(self) -> Option<Self>;
+    /// Number of values in `lo..=hi`, minus one.
+    fn offset(lo: Self, hi: Self) -> u128;
+    /// Moves `self` by `to - from`.
+    fn shift(self, from: Self, to: Self) -> Self;
+}
+
+macro_rules! impl_endpoint {
+    ($wide:ty: $($t:ty),*) => {$(
+        impl Endpoint for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn succ(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn pred(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn offset(lo: Self, hi: Self) -> u128 {
+                (hi as $wide).wrapping_sub(lo as $wide) as u128
+            }
+
+            fn shift(self, from: Self, to: Self) -> Self {
+                to.wrapping_add(self.wrapping_sub(from))
+            }
+        }
+    )*};
+}
+
+impl_endpoint!(u128: u8, u16, u32, u64, u128, usize);
+impl_endpoint!(i128: i8, i16, i32, i64, i128, isize);
+
+/// Inclusive ends of `range`, or `None` if it is empty.
+fn ends<T: Endpoint>(range: impl RangeBounds<T>) -> Option<(T, T)> {
+    let lo = match range.start_bound() {
+        Bound::Included(&lo) => lo,
+        Bound::Excluded(&lo) => lo.succ()?,
+        Bound::Unbounded => T::MIN,
+    };
+    let hi = match range.end_bound() {
+        Bound::Included(&hi) => hi,
+        Bound::Excluded(&hi) => hi.pred()?,
+        Bound::Unbounded => T::MAX,
+    };
+    (lo <= hi).then_some((lo, hi))
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent inclusive ranges.
+///
+/// Every method taking a range accepts any bounds, so `a..b`, `a..=b` and `a..`
+/// all work.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges ranges sorted by start into a normalized set.
+    fn from_sorted(sorted: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = Vec::new();
+        for (lo, hi) in sorted {
+            match ranges.last_mut() {
+                Some((_, last)) if last.succ().is_none_or(|next| lo <= next) => {
+                    *last = hi.max(*last);
+                }
+                _ => ranges.push((lo, hi)),
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn insert(&mut self, range: impl RangeBounds<T>) {
+        let Some((lo, hi)) = ends(range) else {
+            return;
+        };
+        // Ranges that overlap or touch the new one
+        let first = self
+            .ranges
+            .partition_point(|&(_, end)| end.succ().is_some_and(|next| next < lo));
+        let last = self
+            .ranges
+            .partition_point(|&(start, _)| hi.succ().is_none_or(|next| start <= next));
+        let merged = match self.ranges[first..last] {
+            [] => (lo, hi),
+            [(start, _), .., (_, end)] | [(start, end)] => (lo.min(start), hi.max(end)),
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: impl RangeBounds<T>) {
+        let Some((lo, hi)) = ends(range) else {
+            return;
+        };
+        let first = self.ranges.partition_point(|&(_, end)| end < lo);
+        let last = self.ranges.partition_point(|&(start, _)| start <= hi);
+        let mut kept = Vec::new();
+        if let Some(&(start, _)) = self.ranges[first..last].first()
+            && start < lo
+        {
+            kept.push((start, lo.pred().unwrap()));
+        }
+        if let Some(&(_, end)) = self.ranges[first..last].last()
+            && end > hi
+        {
+            kept.push((hi.succ().unwrap(), end));
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Whether every value in `range` is in the set. Empty ranges always are.
+    pub fn covers(&self, range: impl RangeBounds<T>) -> bool {
+        let Some((lo, hi)) = ends(range) else {
+            return true;
+        };
+        let i = self.ranges.partition_point(|&(_, end)| end < lo);
+        self.ranges
+            .get(i)
+            .is_some_and(|&(start, end)| start <= lo && hi <= end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set. Panics if that doesn't fit in a `u128`,
+    /// which only the full `u128` or `i128` range can do.
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|&(lo, hi)| T::offset(lo, hi).checked_add(1))
+            .try_fold(0u128, |total, n| total.checked_add(n?))
+            .expect("Set size should fit in u128")
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|&(lo, _)| lo)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|&(_, hi)| hi)
+    }
+
+    /// The ranges in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(lo, hi)| lo..=hi)
+    }
+
+    /// Every value of `T` not in the set.
+    pub fn complement(&self) -> Self {
+        let mut ranges = Vec::new();
+        let mut next = Some(T::MIN);
+        for &(lo, hi) in &self.ranges {
+            if let Some(start) = next
+                && start < lo
+            {
+                ranges.push((start, lo.pred().unwrap()));
+            }
+            next = hi.succ();
+        }
+        ranges.extend(next.map(|start| (start, T::MAX)));
+        Self { ranges }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        let sorted = std::iter::from_fn(|| match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if y.0 < x.0 => b.next(),
+            (Some(_), _) => a.next(),
+            _ => b.next(),
+        });
+        Self::from_sorted(sorted.copied())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(&(a_lo, a_hi)), Some(&(b_lo, b_hi))) =
+            (self.ranges.get(i), other.ranges.get(j))
+        {
+            let (lo, hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+            if lo <= hi {
+                ranges.push((lo, hi));
+            }
+            if a_hi < b_hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// Moves every value by `to - from`. Values pushed out of `T` wrap around.
+    pub fn translate(&self, from: T, to: T) -> Self {
+        let mut shifted: Vec<_> = self
+            .ranges
+            .iter()
+            .map(|&(lo, hi)| (lo.shift(from, to), hi.shift(from, to)))
+            .collect();
+        // Wrapping can split a range or reorder the set
+        if shifted.iter().any(|&(lo, hi)| lo > hi) {
+            shifted = shifted
+                .into_iter()
+                .flat_map(|(lo, hi)| {
+                    if lo <= hi {
+                        [Some((lo, hi)), None]
+                    } else {
+                        [Some((lo, T::MAX)), Some((T::MIN, hi))]
+                    }
+                })
+                .flatten()
+                .collect();
+        }
+        shifted.sort_unstable();
+        Self::from_sorted(shifted)
+    }
+
+    /// Splits the set by the ranges of `table`: the part each range covers, with
+    /// earlier ranges taking precedence, then the part none of them cover.
+    pub fn split<R: RangeBounds<T>>(
+        &self,
+        table: impl IntoIterator<Item = R>,
+    ) -> (Vec<Self>, Self) {
+        let mut rest = self.clone();
+        let parts = table
+            .into_iter()
+            .map(|range| {
+                let part = rest.intersection(&IntervalSet::from_iter([range]));
+                rest = rest.difference(&part);
+                part
+            })
+            .collect();
+        (parts, rest)
+    }
+
+    /// Sends values through a mapping table of source ranges and the value each
+    /// range's start maps to. Values outside every source range map to themselves.
+    pub fn map<R: RangeBounds<T>>(&self, table: impl IntoIterator<Item = (R, T)>) -> Self {
+        let (sources, targets): (Vec<_>, Vec<_>) = table
+            .into_iter()
+            .map(|(range, to)| {
+                let start = (range.start_bound().cloned(), Bound::Unbounded);
+                let from = ends(start).map_or(T::MAX, |(lo, _)| lo);
+                (range, (from, to))
+            })
+            .unzip();
+        let (parts, rest) = self.split(sources);
+        parts
+            .iter()
+            .zip(targets)
+            .fold(rest, |mapped, (part, (from, to))| {
+                mapped.union(&part.translate(from, to))
+            })
+    }
+}
+
+impl<T: Endpoint, R: RangeBounds<T>> Extend<R> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Endpoint, R: RangeBounds<T>> FromIterator<R> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter.into_iter().filter_map(ends).collect();
+        sorted.sort_unstable();
+        Self::from_sorted(sorted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use rstest::rstest;
+
+    /// The same set as a bitmap over every u8.
+    fn bits(set: &IntervalSet<u8>) -> [bool; 256] {
+        std::array::from_fn(|i| set.contains(i as u8))
+    }
+
+    fn random_set(rng: &mut Rng) -> IntervalSet<u8> {
+        let mut next = || rng.below(256) as u8;
+        let mut set = IntervalSet::new();
+        for _ in 0..6 {
+            let (a, b) = (next(), next());
+            if next() % 3 == 0 {
+                set.remove(a.min(b)..=a.max(b));
+            } else {
+                set.insert(a.min(b)..=a.max(b));
+            }
+        }
+        set
+    }
+
+    #[rstest]
+    #[case(vec![1..3, 5..7], vec![1..=2, 5..=6])]
+    #[case(vec![1..3, 3..7], vec![1..=6])]
+    #[case(vec![5..7, 1..5], vec![1..=6])]
+    #[case(vec![1..2, 5..6, 3..4, 0..10], vec![0..=9])]
+    #[case(vec![4..4, 7..7], vec![])]
+    fn test_insert(
+        #[case] ranges: Vec<std::ops::Range<u32>>,
+        #[case] expected: Vec<RangeInclusive<u32>>,
+    ) {
+        let mut set = IntervalSet::new();
+        set.extend(ranges.iter().cloned());
+        assert_eq!(set.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(IntervalSet::from_iter(ranges), set);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = IntervalSet::from_iter([0..=20, 30..=40]);
+        set.remove(5..10);
+        set.remove(18..=32);
+        set.remove(40..);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![0..=4, 10..=17, 33..=39]
+        );
+        assert_eq!(set.len(), 5 + 8 + 7);
+        assert!(set.contains(33) && !set.contains(32));
+        assert!(set.covers(10..18) && !set.covers(10..=18) && set.covers(3..3));
+    }
+
+    #[test]
+    fn test_extremes() {
+        let mut set = IntervalSet::<i8>::new();
+        set.insert(..);
+        assert_eq!(set.len(), 256);
+        set.remove(..=-100);
+        set.remove(100..);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![-99..=99]);
+        assert_eq!(
+            set.complement().iter().collect::<Vec<_>>(),
+            vec![-128..=-100, 100..=127]
+        );
+        assert_eq!(
+            set.translate(0, 100).iter().collect::<Vec<_>>(),
+            vec![-128..=-57, 1..=127]
+        );
+        assert!(
+            IntervalSet::<i8>::from_iter([(Bound::Excluded(127), Bound::Unbounded)]).is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Set size should fit in u128")]
+    fn test_len_overflow() {
+        IntervalSet::<u128>::from_iter([..]).len();
+    }
+
+    #[test]
+    fn test_set_ops_against_bitmap() {
+        let mut rng = Rng::default();
+        for _ in 0..200 {
+            let (a, b) = (random_set(&mut rng), random_set(&mut rng));
+            let (x, y) = (bits(&a), bits(&b));
+            let check = |set: IntervalSet<u8>, f: fn(bool, bool) -> bool| {
+                let expected: [bool; 256] = std::array::from_fn(|i| f(x[i], y[i]));
+                assert_eq!(bits(&set), expected);
+                assert_eq!(set.len(), expected.iter().filter(|&&b| b).count() as u128);
+                // Normalized: no empty, touching or unordered ranges
+                assert!(
+                    set.ranges
+                        .windows(2)
+                        .all(|w| w[0].1 as u16 + 1 < w[1].0 as u16)
+                );
+            };
+            check(a.union(&b), |x, y| x || y);
+            check(a.intersection(&b), |x, y| x && y);
+            check(a.difference(&b), |x, y| x && !y);
+            check(a.complement(), |x, _| !x);
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let mut set = IntervalSet::new();
+        set.insert(0..100u64);
+        let (parts, rest) = set.split([10..20, 15..30, 90..200]);
+        let parts: Vec<Vec<_>> = parts.iter().map(|p| p.iter().collect()).collect();
+        assert_eq!(parts, vec![vec![10..=19], vec![20..=29], vec![90..=99]]);
+        assert_eq!(rest.iter().collect::<Vec<_>>(), vec![0..=9, 30..=89]);
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        // 2023 day 5 example: maps of "destination source length" lines
+        let maps: [&[[u64; 3]]; 7] = [
+            &[[50, 98, 2], [52, 50, 48]],
+            &[[0, 15, 37], [37, 52, 2], [39, 0, 15]],
+            &[[49, 53, 8], [0, 11, 42], [42, 0, 7], [57, 7, 4]],
+            &[[88, 18, 7], [18, 25, 70]],
+            &[[45, 77, 23], [81, 45, 19], [68, 64, 13]],
+            &[[0, 69, 1], [1, 0, 69]],
+            &[[60, 56, 37], [56, 93, 4]],
+        ];
+        let seeds = IntervalSet::from_iter([79..79 + 14, 55..55 + 13]);
+        let locations = maps.iter().fold(seeds, |set, map| {
+            set.map(map.iter().map(|&[to, from, len]| (from..from + len, to)))
+        });
+        assert_eq!(locations.min(), Some(46));
+        assert_eq!(locations.len(), 27);
+    }
+}
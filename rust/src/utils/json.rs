@@ -0,0 +1,367 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parse::numbers;
+
+/// A JSON value borrowing its strings and numbers from the source text.
+///
+/// Strings are kept as written, escapes included, since puzzle inputs rarely
+/// have any. Objects keep their keys in order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(&'a str),
+    String(&'a str),
+    Array(Vec<Value<'a>>),
+    Object(Vec<(&'a str, Value<'a>)>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset where parsing failed.
+    pub pos: usize,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Expected {} at byte {}", self.expected, self.pos)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, expected: &'static str) -> Result<T, ParseError> {
+        Err(ParseError {
+            pos: self.pos,
+            expected,
+        })
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        let bytes = self.text.as_bytes();
+        while bytes.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+        bytes.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        self.pos += found as usize;
+        found
+    }
+
+    fn literal(&mut self, word: &str, value: Value<'a>) -> Result<Value<'a>, ParseError> {
+        if self.text[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            self.error("a value")
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a str, ParseError> {
+        if !self.eat(b'"') {
+            return self.error("a string");
+        }
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        while let Some(&byte) = bytes.get(self.pos) {
+            match byte {
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(&self.text[start..self.pos - 1]);
+                }
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+        // A trailing backslash steps past the end
+        self.pos = self.pos.min(self.text.len());
+        self.error("a closing quote")
+    }
+
+    /// Steps over the byte at `pos` if it is one of `set`. Whitespace isn't
+    /// skipped first.
+    fn skip(&mut self, set: &[u8]) -> bool {
+        let found = self
+            .text
+            .as_bytes()
+            .get(self.pos)
+            .is_some_and(|b| set.contains(b));
+        self.pos += found as usize;
+        found
+    }
+
+    /// Steps over a run of digits, returning its length.
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.skip(b"0123456789") {}
+        self.pos - start
+    }
+
+    /// `-? (0 | [1-9][0-9]*) (.[0-9]+)? ([eE][+-]?[0-9]+)?`
+    fn number(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.pos;
+        self.skip(b"-");
+        let leading_zero = self.text.as_bytes().get(self.pos) == Some(&b'0');
+        let int = self.digits();
+        let valid = (int == 1 || int > 1 && !leading_zero)
+            && (!self.skip(b".") || self.digits() > 0)
+            && (!self.skip(b"eE") || {
+                self.skip(b"+-");
+                self.digits() > 0
+            });
+        if !valid {
+            self.pos = start;
+            return self.error("a number");
+        }
+        Ok(&self.text[start..self.pos])
+    }
+
+    /// Items of an array or object up to `close`, the opening bracket eaten.
+    fn items<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            if !self.eat(b',') {
+                return self.error("a comma or closing bracket");
+            }
+        }
+    }
+
+    /// Nests by recursion, which is fine for anything in a puzzle input.
+    fn value(&mut self) -> Result<Value<'a>, ParseError> {
+        match self.peek() {
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                self.items(b']', Self::value).map(Value::Array)
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let entries = self.items(b'}', |p| {
+                    let key = p.string()?;
+                    if !p.eat(b':') {
+                        return p.error("a colon");
+                    }
+                    Ok((key, p.value()?))
+                });
+                entries.map(Value::Object)
+            }
+            Some(b'-' | b'0'..=b'9') => self.number().map(Value::Number),
+            _ => self.error("a value"),
+        }
+    }
+}
+
+/// Parses a whole JSON document.
+pub fn parse(text: &str) -> Result<Value<'_>, ParseError> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(_) => parser.error("the end of input"),
+    }
+}
+
+/// Sum of every integer in the text, without building a tree. Only right for
+/// input with integer numbers and no digits inside strings: those count too,
+/// so `1.5e3` sums to 9.
+pub fn sum_numbers(text: &str) -> i64 {
+    numbers::<i64, _>(text).sum()
+}
+
+impl<'a> Value<'a> {
+    /// The number as an integer, if it is one that fits in `T`.
+    pub fn as_int<T: FromStr>(&self) -> Option<T> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(&'a str, Value<'a>)]> {
+        match self {
+            Value::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The value under `key`, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.as_object()?
+            .iter()
+            .find_map(|(k, v)| (*k == key).then_some(v))
+    }
+
+    /// Direct children: array items or object values.
+    pub fn children(&self) -> impl Iterator<Item = &Value<'a>> {
+        let (items, entries) = match self {
+            Value::Array(items) => (&items[..], &[][..]),
+            Value::Object(entries) => (&[][..], &entries[..]),
+            _ => (&[][..], &[][..]),
+        };
+        items.iter().chain(entries.iter().map(|(_, v)| v))
+    }
+
+    /// Calls `f` on this value and its descendants, parents first. Returning
+    /// false skips the value's children.
+    pub fn visit(&self, f: &mut impl FnMut(&Value<'a>) -> bool) {
+        if f(self) {
+            for child in self.children() {
+                child.visit(f);
+            }
+        }
+    }
+
+    /// Folds the tree bottom-up. `f` gets each value with an iterator folding
+    /// its children on demand, so not consuming it prunes the subtree.
+    pub fn fold<T>(&self, f: &impl Fn(&Value<'a>, &mut dyn Iterator<Item = T>) -> T) -> T {
+        f(self, &mut self.children().map(|child| child.fold(f)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// 2015 day 12 part 2: objects with a "red" value don't count.
+    fn sum_without_red(value: &Value) -> i64 {
+        value.fold(&|v, children| match v {
+            Value::Number(_) => v.as_int().unwrap(),
+            Value::Object(entries) if entries.iter().any(|(_, v)| v.as_str() == Some("red")) => 0,
+            _ => children.sum(),
+        })
+    }
+
+    #[rstest]
+    #[case("[1,2,3]", 6, 6)]
+    #[case(r#"{"a":2,"b":4}"#, 6, 6)]
+    #[case("[[[3]]]", 3, 3)]
+    #[case(r#"{"a":{"b":4},"c":-1}"#, 3, 3)]
+    #[case(r#"{"a":[-1,1]}"#, 0, 0)]
+    #[case(r#"[-1,{"a":1}]"#, 0, 0)]
+    #[case("[]", 0, 0)]
+    #[case("{}", 0, 0)]
+    #[case(r#"[1,{"c":"red","b":2},3]"#, 6, 4)]
+    #[case(r#"{"d":"red","e":[1,2,3,4],"f":5}"#, 15, 0)]
+    #[case(r#"[1,"red",5]"#, 6, 6)]
+    fn test_day_12(#[case] input: &str, #[case] all: i64, #[case] without_red: i64) {
+        assert_eq!(sum_numbers(input), all);
+        let value = parse(input).unwrap();
+        assert_eq!(
+            value.fold(&|v, children| v.as_int().unwrap_or(0) + children.sum::<i64>()),
+            all
+        );
+        assert_eq!(sum_without_red(&value), without_red);
+    }
+
+    #[test]
+    fn test_parse() {
+        let text =
+            r#" {"name": "a \"b\"", "list": [true, false, null, -1.5e3, 20], "empty": {} } "#;
+        let value = parse(text).unwrap();
+        assert_eq!(
+            value.get("name").and_then(Value::as_str),
+            Some(r#"a \"b\""#)
+        );
+        let list = value.get("list").and_then(Value::as_array).unwrap();
+        assert_eq!(
+            list[..3],
+            [Value::Bool(true), Value::Bool(false), Value::Null]
+        );
+        assert_eq!(
+            (list[3].as_f64(), list[3].as_int::<i64>()),
+            (Some(-1500.0), None)
+        );
+        assert_eq!(list[4].as_int::<u8>(), Some(20));
+        assert_eq!(Value::Number("-1").as_int::<u8>(), None);
+        assert_eq!(Value::Number("300").as_int::<u8>(), None);
+
+        let floats = parse("[-0, 0.5e1, 2E-2, 1.5e3]").unwrap();
+        let floats: Vec<f64> = floats.children().filter_map(Value::as_f64).collect();
+        assert_eq!(floats, vec![0.0, 5.0, 0.02, 1500.0]);
+        // The fast path only knows integers
+        assert_eq!(sum_numbers("[1.5e3]"), 9);
+        assert_eq!(value.get("empty"), Some(&Value::Object(vec![])));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn test_visit_prunes() {
+        let value = parse(r#"{"a":[1,{"skip":[2,3]}],"b":4}"#).unwrap();
+        let mut seen = Vec::new();
+        value.visit(&mut |v| {
+            seen.extend(v.as_int::<i64>());
+            v.get("skip").is_none()
+        });
+        assert_eq!(seen, vec![1, 4]);
+    }
+
+    #[rstest]
+    #[case("", 0, "a value")]
+    #[case("[1,]", 3, "a value")]
+    #[case("[1 2]", 3, "a comma or closing bracket")]
+    #[case(r#"{"a" 1}"#, 5, "a colon")]
+    #[case(r#"{1:2}"#, 1, "a string")]
+    #[case(r#"["abc"#, 5, "a closing quote")]
+    #[case(r#""ab\"#, 4, "a closing quote")]
+    #[case("[1] x", 4, "the end of input")]
+    #[case("nul", 0, "a value")]
+    #[case("-", 0, "a number")]
+    #[case("--1", 0, "a number")]
+    #[case("1e+-", 0, "a number")]
+    #[case("[1.]", 1, "a number")]
+    #[case("01", 0, "a number")]
+    #[case("1-2", 1, "the end of input")]
+    #[case("[1-2]", 2, "a comma or closing bracket")]
+    #[case("[.5]", 1, "a value")]
+    fn test_errors(#[case] input: &str, #[case] pos: usize, #[case] expected: &str) {
+        let error = parse(input).unwrap_err();
+        assert_eq!((error.pos, error.expected), (pos, expected));
+    }
+}
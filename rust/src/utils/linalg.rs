@@ -0,0 +1,478 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Index, IndexMut, Mul, Sub};
+
+use crate::rational::Ratio;
+
+/// Matrix entries: anything with exact addition, subtraction and multiplication.
+pub trait Scalar:
+    Copy + PartialEq + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+/// Scalars that can also divide, as elimination needs.
+pub trait Field: Scalar + Div<Output = Self> {}
+
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {$(
+        impl Scalar for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        }
+    )*};
+}
+
+impl_scalar!(i32, i64, i128, u32, u64, u128);
+
+macro_rules! impl_field {
+    ($($t:ty),*) => {$(
+        impl Scalar for Ratio<$t> {
+            const ZERO: Self = Ratio::<$t>::ZERO;
+            const ONE: Self = Ratio::<$t>::ONE;
+        }
+
+        impl Field for Ratio<$t> {}
+    )*};
+}
+
+impl_field!(i32, i64, i128);
+
+/// Outcome of solving `A x = b`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Solution<T> {
+    Unique(Vec<T>),
+    /// No `x` satisfies every equation.
+    Inconsistent,
+    /// Infinitely many solutions: `particular` is the one with every free
+    /// variable zero.
+    Underdetermined {
+        particular: Vec<T>,
+        free: Vec<usize>,
+    },
+}
+
+/// A dense row-major matrix sized at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Scalar> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![T::ZERO; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        (0..n).for_each(|i| m[(i, i)] = T::ONE);
+        m
+    }
+
+    pub fn from_rows<R: AsRef<[T]>>(rows: &[R]) -> Self {
+        let cols = rows.first().map_or(0, |row| row.as_ref().len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            assert_eq!(
+                row.as_ref().len(),
+                cols,
+                "All rows should have the same width"
+            );
+            data.extend_from_slice(row.as_ref());
+        }
+        Self {
+            rows: rows.len(),
+            cols,
+            data,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn map<U: Scalar>(&self, f: impl FnMut(T) -> U) -> Matrix<U> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().copied().map(f).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+        t
+    }
+
+    pub fn mul_vec(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(v.len(), self.cols, "Vector length should match the columns");
+        (0..self.rows)
+            .map(|i| {
+                self.row(i)
+                    .iter()
+                    .zip(v)
+                    .fold(T::ZERO, |acc, (&a, &b)| acc + a * b)
+            })
+            .collect()
+    }
+
+    /// `self` multiplied by itself `n` times, by repeated squaring.
+    pub fn pow(&self, mut n: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "Only square matrices have powers");
+        let mut result = Self::identity(self.rows);
+        let mut base = self.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = &result * &base;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T: Field> Matrix<T> {
+    /// Gauss-Jordan elimination to reduced row echelon form, returning the
+    /// pivot column of each non-zero row and whether an odd number of row
+    /// swaps happened.
+    fn reduce(&mut self) -> (Vec<usize>, bool) {
+        let mut pivots = Vec::new();
+        let mut swapped = false;
+        for col in 0..self.cols {
+            let row = pivots.len();
+            let Some(pivot) = (row..self.rows).find(|&r| self[(r, col)] != T::ZERO) else {
+                continue;
+            };
+            if pivot != row {
+                for j in 0..self.cols {
+                    self.data.swap(pivot * self.cols + j, row * self.cols + j);
+                }
+                swapped = !swapped;
+            }
+            let p = self[(row, col)];
+            for j in 0..self.cols {
+                self[(row, j)] = self[(row, j)] / p;
+            }
+            for r in (0..self.rows).filter(|&r| r != row) {
+                let factor = self[(r, col)];
+                if factor != T::ZERO {
+                    for j in 0..self.cols {
+                        self[(r, j)] = self[(r, j)] - factor * self[(row, j)];
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        (pivots, swapped)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduce().0.len()
+    }
+
+    pub fn determinant(&self) -> T {
+        assert_eq!(
+            self.rows, self.cols,
+            "Only square matrices have determinants"
+        );
+        // Product of the pivots, by plain elimination without normalizing rows
+        let mut m = self.clone();
+        let mut det = T::ONE;
+        for col in 0..m.cols {
+            let Some(pivot) = (col..m.rows).find(|&r| m[(r, col)] != T::ZERO) else {
+                return T::ZERO;
+            };
+            if pivot != col {
+                for j in 0..m.cols {
+                    m.data.swap(pivot * m.cols + j, col * m.cols + j);
+                }
+                det = T::ZERO - det;
+            }
+            let p = m[(col, col)];
+            det = det * p;
+            for r in col + 1..m.rows {
+                let factor = m[(r, col)] / p;
+                for j in col..m.cols {
+                    m[(r, j)] = m[(r, j)] - factor * m[(col, j)];
+                }
+            }
+        }
+        det
+    }
+
+    /// Solves `self * x = b` exactly.
+    pub fn solve(&self, b: &[T]) -> Solution<T> {
+        assert_eq!(
+            b.len(),
+            self.rows,
+            "Right-hand side should have one value per row"
+        );
+        // Augmented matrix [A | b]
+        let mut m = Self::zeros(self.rows, self.cols + 1);
+        for i in 0..self.rows {
+            m.data[i * m.cols..(i + 1) * m.cols - 1].copy_from_slice(self.row(i));
+            m[(i, self.cols)] = b[i];
+        }
+        let (pivots, _) = m.reduce();
+        if pivots.last() == Some(&self.cols) {
+            return Solution::Inconsistent;
+        }
+
+        let mut x = vec![T::ZERO; self.cols];
+        for (row, &col) in pivots.iter().enumerate() {
+            x[col] = m[(row, self.cols)];
+        }
+        if pivots.len() == self.cols {
+            Solution::Unique(x)
+        } else {
+            let free = (0..self.cols).filter(|c| !pivots.contains(c)).collect();
+            Solution::Underdetermined {
+                particular: x,
+                free,
+            }
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Scalar> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: Self) -> Matrix<T> {
+        assert_eq!(self.cols, rhs.rows, "Inner dimensions should match");
+        let mut product = Matrix::zeros(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                for j in 0..rhs.cols {
+                    product[(i, j)] = product[(i, j)] + a * rhs[(k, j)];
+                }
+            }
+        }
+        product
+    }
+}
+
+/// A matrix sized at compile time, stored as rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedMatrix<T, const R: usize, const C: usize>(pub [[T; C]; R]);
+
+impl<T: Scalar, const R: usize, const C: usize> FixedMatrix<T, R, C> {
+    pub fn transpose(&self) -> FixedMatrix<T, C, R> {
+        FixedMatrix(std::array::from_fn(|j| {
+            std::array::from_fn(|i| self.0[i][j])
+        }))
+    }
+
+    pub fn mul_vec(&self, v: [T; C]) -> [T; R] {
+        self.0
+            .map(|row| row.iter().zip(v).fold(T::ZERO, |acc, (&a, b)| acc + a * b))
+    }
+}
+
+impl<T: Scalar, const N: usize> FixedMatrix<T, N, N> {
+    pub fn identity() -> Self {
+        Self(std::array::from_fn(|i| {
+            std::array::from_fn(|j| if i == j { T::ONE } else { T::ZERO })
+        }))
+    }
+
+    /// `self` multiplied by itself `n` times, by repeated squaring.
+    pub fn pow(self, mut n: u64) -> Self {
+        let (mut result, mut base) = (Self::identity(), self);
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result * base;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base * base;
+            }
+        }
+        result
+    }
+}
+
+impl<T: Field, const N: usize> FixedMatrix<T, N, N> {
+    pub fn determinant(&self) -> T {
+        Matrix::from(*self).determinant()
+    }
+
+    /// Solves `self * x = b` exactly, as [`Matrix::solve`] does.
+    pub fn solve(&self, b: [T; N]) -> Solution<T> {
+        Matrix::from(*self).solve(&b)
+    }
+}
+
+impl<T: Scalar, const R: usize, const K: usize, const C: usize> Mul<FixedMatrix<T, K, C>>
+    for FixedMatrix<T, R, K>
+{
+    type Output = FixedMatrix<T, R, C>;
+
+    fn mul(self, rhs: FixedMatrix<T, K, C>) -> Self::Output {
+        FixedMatrix(std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..K).fold(T::ZERO, |acc, k| acc + self.0[i][k] * rhs.0[k][j]))
+        }))
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> From<FixedMatrix<T, R, C>> for Matrix<T> {
+    fn from(m: FixedMatrix<T, R, C>) -> Self {
+        Matrix::from_rows(&m.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    type Q = Ratio<i128>;
+
+    fn q(n: i128) -> Q {
+        Q::from(n)
+    }
+
+    fn ratios<const N: usize>(rows: &[[i128; N]]) -> Matrix<Q> {
+        Matrix::from_rows(rows).map(q)
+    }
+
+    /// Determinant by cofactor expansion along the first row.
+    fn cofactor(m: &Matrix<i64>) -> i64 {
+        let n = m.rows();
+        if n == 0 {
+            return 1;
+        }
+        (0..n)
+            .map(|j| {
+                let minor: Vec<Vec<i64>> = (1..n)
+                    .map(|i| (0..n).filter(|&c| c != j).map(|c| m[(i, c)]).collect())
+                    .collect();
+                let sign = if j % 2 == 0 { 1 } else { -1 };
+                sign * m[(0, j)] * cofactor(&Matrix::from_rows(&minor))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_determinant_against_cofactors() {
+        let mut rng = Rng::default();
+        for n in 1..=5 {
+            for _ in 0..40 {
+                let data: Vec<Vec<i64>> = (0..n)
+                    .map(|_| (0..n).map(|_| rng.below(7) as i64 - 3).collect())
+                    .collect();
+                let m = Matrix::from_rows(&data);
+                let det = m.map(|x| Ratio::from(x as i128)).determinant();
+                assert_eq!(det, q(cofactor(&m) as i128));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "All rows should have the same width")]
+    fn test_ragged_rows() {
+        // Six elements, as a 3x2 matrix would have
+        let rows: [&[i64]; 3] = [&[1, 2], &[3], &[4, 5, 6]];
+        Matrix::from_rows(&rows);
+    }
+
+    #[test]
+    fn test_solve_unique() {
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let a = ratios(&[[2, 1, -1], [-3, -1, 2], [-2, 1, 2]]);
+        let x = vec![q(2), q(3), q(-1)];
+        assert_eq!(a.solve(&[q(8), q(-11), q(-3)]), Solution::Unique(x.clone()));
+        assert_eq!(a.mul_vec(&x), vec![q(8), q(-11), q(-3)]);
+
+        // Fractions stay exact: x + y = 1, x - y = 1/3
+        let a = ratios(&[[1, 1], [1, -1]]);
+        let b = [Q::ONE, Q::new(1, 3)];
+        assert_eq!(
+            a.solve(&b),
+            Solution::Unique(vec![Q::new(2, 3), Q::new(1, 3)])
+        );
+    }
+
+    #[test]
+    fn test_solve_degenerate() {
+        let a = ratios(&[[1, 2], [2, 4]]);
+        assert_eq!(a.solve(&[q(3), q(7)]), Solution::Inconsistent);
+        assert_eq!(
+            a.solve(&[q(3), q(6)]),
+            Solution::Underdetermined {
+                particular: vec![q(3), q(0)],
+                free: vec![1]
+            }
+        );
+        assert_eq!(a.rank(), 1);
+        assert_eq!(a.determinant(), Q::ZERO);
+
+        // More equations than unknowns, but consistent
+        let a = ratios(&[[1, 0], [0, 1], [1, 1]]);
+        assert_eq!(
+            a.solve(&[q(1), q(2), q(3)]),
+            Solution::Unique(vec![q(1), q(2)])
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let fib = FixedMatrix([[1u64, 1], [1, 0]]);
+        assert_eq!(fib.pow(90).0[0][1], 2880067194370816120);
+        assert_eq!(fib.pow(0), FixedMatrix::identity());
+        let dynamic = Matrix::from(fib);
+        assert_eq!(dynamic.pow(90)[(0, 1)], 2880067194370816120);
+        assert_eq!(dynamic.pow(5), Matrix::from(fib.pow(5)));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let a = FixedMatrix([[1i64, 2, 3], [4, 5, 6]]);
+        let b = a.transpose();
+        assert_eq!(a * b, FixedMatrix([[14, 32], [32, 77]]));
+        assert_eq!(a.mul_vec([1, 0, -1]), [-2, -2]);
+
+        let m = FixedMatrix([[q(2), q(1)], [q(1), q(3)]]);
+        assert_eq!(m.determinant(), q(5));
+        assert_eq!(
+            m.solve([q(3), q(5)]),
+            Solution::Unique(vec![Q::new(4, 5), Q::new(7, 5)])
+        );
+    }
+}
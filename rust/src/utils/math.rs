@@ -0,0 +1,292 @@
+use std::fmt::Debug;
+
+/// Integer types with a greatest common divisor.
+pub trait Integer: Copy + Ord + Debug {
+    /// Always non-negative. `gcd(0, 0)` is 0. Signed types panic when the
+    /// result doesn't fit, which only `gcd(MIN, 0)` and `gcd(MIN, MIN)` reach.
+    fn gcd(self, other: Self) -> Self;
+    /// Always non-negative. Zero if either side is, `None` if it doesn't fit.
+    fn lcm(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            fn gcd(self, other: Self) -> Self {
+                let (mut a, mut b) = (self, other);
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+                a
+            }
+
+            fn lcm(self, other: Self) -> Option<Self> {
+                if self == 0 || other == 0 {
+                    return Some(0);
+                }
+                (self / self.gcd(other)).checked_mul(other)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed {
+    ($($t:ty => $u:ty),*) => {$(
+        impl Integer for $t {
+            fn gcd(self, other: Self) -> Self {
+                let g = self.unsigned_abs().gcd(other.unsigned_abs());
+                <$t>::try_from(g).expect("gcd should fit the type")
+            }
+
+            fn lcm(self, other: Self) -> Option<Self> {
+                let l = self.unsigned_abs().lcm(other.unsigned_abs())?;
+                <$t>::try_from(l).ok()
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    a.gcd(b)
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    a.lcm(b)
+}
+
+/// `(g, x, y)` with `a * x + b * y == g`, where `g` is the gcd of `a` and `b`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// `base.pow(exp) % m`, with products in a `u128` so nothing overflows.
+pub fn modpow(base: u64, mut exp: u64, m: u64) -> u64 {
+    let m = m as u128;
+    let mut base = base as u128 % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// `x` with `a * x % m == 1`, if `a` and `m` are coprime.
+pub fn modinv(a: u64, m: u64) -> Option<u64> {
+    let (g, x, _) = extended_gcd(a as i128, m as i128);
+    (g == 1).then(|| x.rem_euclid(m as i128) as u64)
+}
+
+/// Chinese Remainder Theorem: the `x` in `0..lcm` with `x % m == r` for every
+/// `(r, m)`, with `lcm` the lcm of the moduli. Moduli need not be coprime, so
+/// there may be no such `x`. Also `None` if `lcm` doesn't fit in i64.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    let (mut x, mut lcm) = (0i128, 1i128);
+    for (r, m) in congruences {
+        assert!(m > 0, "Moduli should be positive");
+        let (r, m) = (r as i128, m as i128);
+        let (g, p, _) = extended_gcd(lcm, m);
+        if (r - x) % g != 0 {
+            return None;
+        }
+        // x + lcm * k hits r mod m for k = (r - x) / g * p mod (m / g)
+        let k = ((r - x) / g % (m / g)) * p % (m / g);
+        x += lcm * k;
+        // Both factors fit in i64, so the product can't overflow i128
+        lcm = (lcm / g)
+            .checked_mul(m)
+            .filter(|&l| l <= i64::MAX as i128)?;
+        x = x.rem_euclid(lcm);
+    }
+    Some((x as i64, lcm as i64))
+}
+
+/// Whether each of `0..=n` is prime.
+pub fn sieve(n: usize) -> Vec<bool> {
+    let mut is_prime = vec![true; n + 1];
+    is_prime[0] = false;
+    if n >= 1 {
+        is_prime[1] = false;
+    }
+    let mut p = 2;
+    while p * p <= n {
+        if is_prime[p] {
+            for multiple in (p * p..=n).step_by(p) {
+                is_prime[multiple] = false;
+            }
+        }
+        p += 1;
+    }
+    is_prime
+}
+
+/// Primes up to `n` included.
+pub fn primes(n: usize) -> Vec<usize> {
+    (0..)
+        .zip(sieve(n))
+        .filter_map(|(p, is_prime)| is_prime.then_some(p))
+        .collect()
+}
+
+/// Sum of the divisors of each of `0..=n`, 0 included as 0.
+pub fn divisor_sums(n: usize) -> Vec<u64> {
+    let mut sums = vec![0; n + 1];
+    for d in 1..=n {
+        for multiple in (d..=n).step_by(d) {
+            sums[multiple] += d as u64;
+        }
+    }
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn brute_gcd(a: i128, b: i128) -> i128 {
+        let (a, b) = (a.abs(), b.abs());
+        (1..=a.max(b))
+            .rev()
+            .find(|d| a % d == 0 && b % d == 0)
+            .unwrap_or(0)
+    }
+
+    macro_rules! check_types {
+        ($($t:ty),*) => {$(
+            for a in (-100i128..=100).filter_map(|a| <$t>::try_from(a).ok()) {
+                for b in (-100i128..=100).step_by(3).filter_map(|b| <$t>::try_from(b).ok()) {
+                    let g = brute_gcd(a as i128, b as i128);
+                    assert_eq!(gcd(a, b) as i128, g, "gcd({a}, {b})");
+                    let l = if g == 0 { 0 } else { (a as i128 / g * b as i128).abs() };
+                    assert_eq!(lcm(a, b), <$t>::try_from(l).ok(), "lcm({a}, {b})");
+                }
+            }
+            // Scaling both sides scales the gcd, checked near the top of the range
+            let k = <$t>::MAX / 100;
+            for (a, b) in [(12, 18), (7, 5), (64, 96), (0, 9)] {
+                assert_eq!(gcd(a * k, b * k), gcd(a, b) * k);
+            }
+        )*};
+    }
+
+    #[test]
+    fn test_gcd_lcm_all_types() {
+        check_types!(
+            u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+        );
+        assert_eq!(gcd(0u32, 0), 0);
+        assert_eq!(gcd(-12i32, 18), 6);
+        assert_eq!(lcm(-4i64, 6), Some(12));
+        assert_eq!(lcm(0u8, 6), Some(0));
+    }
+
+    #[test]
+    fn test_lcm_overflow() {
+        assert_eq!(lcm(16u8, 17), None);
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(lcm(i8::MIN, 3), None);
+        assert_eq!(lcm(i8::MIN, 2), None);
+        assert_eq!(lcm(u128::MAX, 1), Some(u128::MAX));
+    }
+
+    #[rstest]
+    #[case(i8::MIN, 0)]
+    #[case(i8::MIN, i8::MIN)]
+    #[should_panic(expected = "gcd should fit the type")]
+    fn test_gcd_overflow(#[case] a: i8, #[case] b: i8) {
+        gcd(a, b);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for a in -30..30 {
+            for b in -30..30 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, brute_gcd(a, b));
+                assert_eq!(a * x + b * y, g);
+            }
+        }
+    }
+
+    #[test]
+    fn test_modpow_modinv() {
+        for m in 1..40u64 {
+            for a in 0..40 {
+                let direct = (0..13).fold(1 % m, |acc, _| acc * a % m);
+                assert_eq!(modpow(a, 13, m), direct);
+                let inverse = (0..m).find(|x| a * x % m == 1 % m && gcd(a, m) == 1);
+                assert_eq!(
+                    modinv(a, m),
+                    inverse.filter(|_| m > 1).or((m == 1).then_some(0))
+                );
+            }
+        }
+        // Products past u64 don't overflow
+        let m = (1 << 61) - 1;
+        assert_eq!(modpow(3, m - 1, m), 1);
+        assert_eq!(modinv(u64::MAX - 1, u64::MAX), Some(u64::MAX - 1));
+    }
+
+    #[rstest]
+    #[case(&[(0, 3), (3, 4), (4, 5)], Some((39, 60)))]
+    #[case(&[(2, 4), (4, 6)], Some((10, 12)))]
+    #[case(&[(1, 4), (2, 6)], None)]
+    #[case(&[(-1, 7)], Some((6, 7)))]
+    #[case(&[], Some((0, 1)))]
+    #[case::too_large(&[(0, i64::MAX), (1, i64::MAX - 1)], None)]
+    #[case::large(&[(5, i64::MAX), (5, 7)], Some((5, i64::MAX)))]
+    fn test_crt(#[case] congruences: &[(i64, i64)], #[case] expected: Option<(i64, i64)>) {
+        assert_eq!(crt(congruences.iter().copied()), expected);
+    }
+
+    #[test]
+    fn test_crt_against_brute_force() {
+        for m1 in 1..12 {
+            for m2 in 1..12 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let l = lcm(m1, m2).unwrap();
+                        let x = (0..l).find(|x| x % m1 == r1 && x % m2 == r2);
+                        assert_eq!(crt([(r1, m1), (r2, m2)]), x.map(|x| (x, l)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sieves() {
+        let is_prime = |n: usize| n >= 2 && (2..n).all(|d| !n.is_multiple_of(d));
+        for n in 0..60 {
+            assert_eq!(sieve(n), (0..=n).map(is_prime).collect::<Vec<_>>());
+        }
+        assert_eq!(primes(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+
+        let sums = divisor_sums(500);
+        for (k, &sum) in sums.iter().enumerate() {
+            assert_eq!(sum, (1..=k).filter(|d| k % d == 0).sum::<usize>() as u64);
+        }
+        // 2015 day 20 example: house 8 gets 10 * 15 presents
+        assert_eq!(sums[8] * 10, 150);
+    }
+}
@@ -37,122 +37,356 @@ fn i(b: u32, c: u32, d: u32) -> u32 {
     c ^ (b | !d)
 }
 
+/// One of the 64 operations, applied to every lane.
 #[inline(always)]
-fn step(
-    a: u32,
-    b: u32,
-    c: u32,
-    d: u32,
-    word: u32,
-    k: u32,
-    s: u32,
-    f: fn(u32, u32, u32) -> u32,
-) -> u32 {
-    // a = b + ((a + F(b, c, d) + X[k] + T[i]) <<< s)
-    b.wrapping_add(
-        a.wrapping_add(f(b, c, d))
-            .wrapping_add(word)
-            .wrapping_add(k)
-            .rotate_left(s),
-    )
-}
-
-fn proc_block(input: &[u8; 64], a: &mut u32, b: &mut u32, c: &mut u32, d: &mut u32) {
-    let mut x = [0u32; 16];
-    for (i, chunk) in input.chunks(4).enumerate() {
-        x[i] = u32::from_le_bytes(chunk.try_into().unwrap());
-    }
-
-    let a0 = *a;
-    let b0 = *b;
-    let c0 = *c;
-    let d0 = *d;
-
-    // We iterate 0..16. The index of the word 'g' is just 'i'.
-    for i in 0..16 {
-        *a = step(*a, *b, *c, *d, x[i], K[i], S[i], f);
-        // Rotate variables: (D, A, B, C) becomes new (A, B, C, D) for the next step
-        let temp = *d;
-        *d = *c;
-        *c = *b;
-        *b = *a;
-        *a = temp;
-    }
-
-    // 4. Round 2
-    // Word index pattern: (5*i + 1) % 16
-    for i in 16..32 {
-        *a = step(*a, *b, *c, *d, x[(5 * i + 1) % 16], K[i], S[i], g); // Use function g
-        let temp = *d;
-        *d = *c;
-        *c = *b;
-        *b = *a;
-        *a = temp;
-    }
-
-    // 5. Round 3
-    // Word index pattern: (3*i + 5) % 16
-    for i in 32..48 {
-        let g = (3 * i + 5) % 16;
-        *a = step(*a, *b, *c, *d, x[g], K[i], S[i], h); // Use function h
-        let temp = *d;
-        *d = *c;
-        *c = *b;
-        *b = *a;
-        *a = temp;
-    }
-
-    // 6. Round 4
-    // Word index pattern: (7*i) % 16
-    for i_ in 48..64 {
-        let g = (7 * i_) % 16;
-        *a = step(*a, *b, *c, *d, x[g], K[i_], S[i_], i); // Use function i
-        let temp = *d;
-        *d = *c;
-        *c = *b;
-        *b = *a;
-        *a = temp;
-    }
-
-    // 7. Add back to original state
-    *a = a.wrapping_add(a0);
-    *b = b.wrapping_add(b0);
-    *c = c.wrapping_add(c0);
-    *d = d.wrapping_add(d0);
-}
-
-pub fn hash<T: AsRef<[u8]>>(input: T) -> [u8; 16] {
-    // state
-    let mut a: u32 = 0x67452301;
-    let mut b: u32 = 0xefcdab89;
-    let mut c: u32 = 0x98badcfe;
-    let mut d: u32 = 0x10325476;
-
-    let input = input.as_ref();
-    let original_bit_length = (input.len() as u64) * 8;
-
-    let mut padded = input.to_vec();
-    padded.push(0x80);
-
-    while padded.len() % 64 != 56 {
-        padded.push(0);
-    }
-
-    padded.extend_from_slice(&original_bit_length.to_le_bytes());
-
-    for chunk in padded.chunks_exact(64) {
-        let block: &[u8; 64] = chunk.try_into().unwrap();
-        proc_block(block, &mut a, &mut b, &mut c, &mut d);
-    }
-
-    // Convert back to bytes (Little Endian)
-    let mut result = [0u8; 16];
-    result[0..4].copy_from_slice(&a.to_le_bytes());
-    result[4..8].copy_from_slice(&b.to_le_bytes());
-    result[8..12].copy_from_slice(&c.to_le_bytes());
-    result[12..16].copy_from_slice(&d.to_le_bytes());
+fn step<const N: usize>(
+    [a, b, c, d]: [[u32; N]; 4],
+    word: [u32; N],
+    i: usize,
+    f: impl Fn(u32, u32, u32) -> u32,
+) -> [u32; N] {
+    let mut out = [0; N];
+    for l in 0..N {
+        // a = b + ((a + F(b, c, d) + X[k] + T[i]) <<< s)
+        let sum = a[l]
+            .wrapping_add(f(b[l], c[l], d[l]))
+            .wrapping_add(word[l])
+            .wrapping_add(K[i]);
+        out[l] = b[l].wrapping_add(sum.rotate_left(S[i]));
+    }
+    out
+}
 
-    result
+/// The 16 operations of round `r`, with `k` picking the message word for operation `i`.
+#[inline(always)]
+fn round<const N: usize>(
+    [mut a, mut b, mut c, mut d]: [[u32; N]; 4],
+    x: &[[u32; N]; 16],
+    r: usize,
+    k: impl Fn(usize) -> usize,
+    f: impl Fn(u32, u32, u32) -> u32 + Copy,
+) -> [[u32; N]; 4] {
+    // Rotating the roles of the variables instead of their values: (D, A, B, C)
+    // becomes (A, B, C, D) for the next step. Unrolled by hand so `i` is a
+    // constant in every step.
+    macro_rules! quad {
+        ($i:expr) => {
+            let i = 16 * r + $i;
+            a = step([a, b, c, d], x[k(i)], i, f);
+            d = step([d, a, b, c], x[k(i + 1)], i + 1, f);
+            c = step([c, d, a, b], x[k(i + 2)], i + 2, f);
+            b = step([b, c, d, a], x[k(i + 3)], i + 3, f);
+        };
+    }
+    quad!(0);
+    quad!(4);
+    quad!(8);
+    quad!(12);
+    [a, b, c, d]
+}
+
+/// Compresses one block per lane into `state`. Lanes are independent, so with
+/// `N` of 4 or 8 they interleave, and vectorize when targeting AVX2.
+#[inline(always)]
+fn compress<const N: usize>(state: &mut [[u32; N]; 4], x: &[[u32; N]; 16]) {
+    // Word index pattern: i, (5*i + 1) % 16, (3*i + 5) % 16, (7*i) % 16
+    let v = round(*state, x, 0, |i| i, f);
+    let v = round(v, x, 1, |i| (5 * i + 1) % 16, g);
+    let v = round(v, x, 2, |i| (3 * i + 5) % 16, h);
+    let v = round(v, x, 3, |i| (7 * i) % 16, i);
+
+    // Add back to original state
+    for (state, v) in state.iter_mut().zip(v) {
+        for l in 0..N {
+            state[l] = state[l].wrapping_add(v[l]);
+        }
+    }
+}
+
+fn proc_block(input: &[u8; 64], state: &mut [u32; 4]) {
+    let mut x = [[0u32; 1]; 16];
+    for (i, chunk) in input.chunks_exact(4).enumerate() {
+        x[i] = [u32::from_le_bytes(chunk.try_into().unwrap())];
+    }
+
+    let mut lanes = state.map(|word| [word]);
+    compress(&mut lanes, &x);
+    *state = lanes.map(|[word]| word);
+}
+
+// Initial state
+const INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+const PADDING: [u8; 64] = {
+    let mut padding = [0; 64];
+    padding[0] = 0x80;
+    padding
+};
+
+/// Incremental MD5. Feed data with [`Md5::update`], read the digest with
+/// [`Md5::finalize`]. Cloning after a shared prefix skips re-hashing it.
+#[derive(Clone, Debug)]
+pub struct Md5 {
+    state: [u32; 4],
+    // Input not yet forming a full block
+    buffer: [u8; 64],
+    buffered: usize,
+    length: u64,
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self {
+            state: INIT,
+            buffer: [0; 64],
+            buffered: 0,
+            length: 0,
+        }
+    }
+}
+
+impl Md5 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update<T: AsRef<[u8]>>(&mut self, input: T) {
+        let mut input = input.as_ref();
+        self.length = self.length.wrapping_add(input.len() as u64);
+
+        // Top up a partially filled block first
+        if self.buffered > 0 {
+            let take = (64 - self.buffered).min(input.len());
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&input[..take]);
+            self.buffered += take;
+            input = &input[take..];
+
+            if self.buffered < 64 {
+                return;
+            }
+            proc_block(&self.buffer, &mut self.state);
+            self.buffered = 0;
+        }
+
+        let mut blocks = input.chunks_exact(64);
+        for block in &mut blocks {
+            proc_block(block.try_into().unwrap(), &mut self.state);
+        }
+
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn finalize(mut self) -> Digest {
+        let original_bit_length = self.length.wrapping_mul(8);
+
+        // A 1 bit, then zeros up to 8 bytes short of a block
+        let pad = (64 + 56 - self.buffered - 1) % 64 + 1;
+        self.update(&PADDING[..pad]);
+        self.update(original_bit_length.to_le_bytes());
+        debug_assert_eq!(self.buffered, 0);
+
+        Digest::from_state(self.state)
+    }
+
+    /// Digests of the data so far followed by each of `suffixes`, computed
+    /// `N` at a time in interleaved lanes.
+    pub fn finalize_batch<T: AsRef<[u8]>, const N: usize>(&self, suffixes: &[T; N]) -> [Digest; N] {
+        let head = &self.buffer[..self.buffered];
+        let tails: [&[u8]; N] = std::array::from_fn(|l| suffixes[l].as_ref());
+        let blocks = tails.map(|tail| (head.len() + tail.len() + 8) / 64 + 1);
+
+        let mut state: [[u32; N]; 4] = self.state.map(|word| [word; N]);
+        for j in 0..blocks.into_iter().max().unwrap_or(0) {
+            let mut x = [[0u32; N]; 16];
+            for l in 0..N {
+                let bit_length = (self.length + tails[l].len() as u64).wrapping_mul(8);
+                let block = padded_block(head, tails[l], j, blocks[l], bit_length);
+                load_lane(&mut x, l, &block);
+            }
+
+            let prev = state;
+            compress(&mut state, &x);
+            // Lanes whose message already ended keep their state
+            for l in (0..N).filter(|&l| j >= blocks[l]) {
+                for (state, prev) in state.iter_mut().zip(prev) {
+                    state[l] = prev[l];
+                }
+            }
+        }
+
+        std::array::from_fn(|l| Digest::from_state(state.map(|words| words[l])))
+    }
+}
+
+/// Message words of `block` into lane `l`.
+fn load_lane<const N: usize>(x: &mut [[u32; N]; 16], l: usize, block: &[u8; 64]) {
+    for (w, chunk) in block.chunks_exact(4).enumerate() {
+        x[w][l] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+}
+
+/// Block `j` of `blocks` of the padded message `head ++ tail`.
+fn padded_block(head: &[u8], tail: &[u8], j: usize, blocks: usize, bit_length: u64) -> [u8; 64] {
+    let start = 64 * j;
+    let mut block = [0u8; 64];
+
+    // Copies the part of `part`, placed at `offset` in the message, that falls in this block
+    let mut copy = |part: &[u8], offset: usize| {
+        let from = start.max(offset);
+        let to = (start + 64).min(offset + part.len());
+        if from < to {
+            block[from - start..to - start].copy_from_slice(&part[from - offset..to - offset]);
+        }
+    };
+    copy(head, 0);
+    copy(tail, head.len());
+
+    let len = head.len() + tail.len();
+    if (start..start + 64).contains(&len) {
+        block[len - start] = 0x80;
+    }
+    if j + 1 == blocks {
+        block[56..].copy_from_slice(&bit_length.to_le_bytes());
+    }
+    block
+}
+
+/// Digests of all `inputs`, computed `N` at a time in interleaved lanes. Same
+/// results as calling [`hash`] on each.
+pub fn hash_batch<T: AsRef<[u8]>, const N: usize>(inputs: &[T; N]) -> [Digest; N] {
+    Md5::new().finalize_batch(inputs)
+}
+
+pub fn hash<T: AsRef<[u8]>>(input: T) -> Digest {
+    let mut md5 = Md5::new();
+    md5.update(input);
+    md5.finalize()
+}
+
+/// Decimal digits of `n`, written to the end of `buf`. Mining puzzles hash a
+/// key followed by these.
+pub fn digits(mut n: u64, buf: &mut [u8; 20]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+/// The single padded block holding the hex of `digest`, which is all of its message.
+fn hex_block(digest: &Digest) -> [u8; 64] {
+    let mut block = [0u8; 64];
+    block[..32].copy_from_slice(&digest.to_hex());
+    block[32] = 0x80;
+    block[56..].copy_from_slice(&(32u64 * 8).to_le_bytes());
+    block
+}
+
+/// Hash of `input`, then `rounds` more times the hash of the previous hash's hex.
+pub fn stretch<T: AsRef<[u8]>>(input: T, rounds: usize) -> Digest {
+    let mut digest = hash(input);
+    for _ in 0..rounds {
+        let mut state = INIT;
+        proc_block(&hex_block(&digest), &mut state);
+        digest = Digest::from_state(state);
+    }
+    digest
+}
+
+/// [`stretch`] of all `inputs`, computed `N` at a time in interleaved lanes.
+pub fn stretch_batch<T: AsRef<[u8]>, const N: usize>(
+    inputs: &[T; N],
+    rounds: usize,
+) -> [Digest; N] {
+    let mut digests = hash_batch(inputs);
+    for _ in 0..rounds {
+        let mut x = [[0u32; N]; 16];
+        for (l, digest) in digests.iter().enumerate() {
+            load_lane(&mut x, l, &hex_block(digest));
+        }
+
+        let mut state = INIT.map(|word| [word; N]);
+        compress(&mut state, &x);
+        digests = std::array::from_fn(|l| Digest::from_state(state.map(|words| words[l])));
+    }
+    digests
+}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// An MD5 hash, printed as lowercase hex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest(pub [u8; 16]);
+
+impl Digest {
+    fn from_state(state: [u32; 4]) -> Self {
+        // Convert back to bytes (Little Endian)
+        let mut result = [0u8; 16];
+        for (chunk, word) in result.chunks_exact_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        Self(result)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Lowercase hex digits, without allocating.
+    pub fn to_hex(&self) -> [u8; 32] {
+        let mut hex = [0u8; 32];
+        for (pair, byte) in hex.chunks_exact_mut(2).zip(self.0) {
+            pair[0] = HEX[(byte >> 4) as usize];
+            pair[1] = HEX[(byte & 0x0F) as usize];
+        }
+        hex
+    }
+
+    /// The `i`-th hex digit's value, `i` in `0..32`.
+    pub fn nibble(&self, i: usize) -> u8 {
+        let byte = self.0[i / 2];
+        if i.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0F
+        }
+    }
+
+    pub fn leading_zero_nibbles(&self) -> u32 {
+        u128::from_be_bytes(self.0).leading_zeros() / 4
+    }
+}
+
+impl From<Digest> for [u8; 16] {
+    fn from(digest: Digest) -> Self {
+        digest.0
+    }
+}
+
+impl std::fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = self.to_hex();
+        // Only ASCII hex digits were written
+        f.pad(std::str::from_utf8(&hex).unwrap())
+    }
+}
+
+impl std::fmt::Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl std::fmt::Debug for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Digest({self})")
+    }
 }
 
 #[cfg(test)]
@@ -162,8 +396,126 @@ mod tests {
 
     #[rstest]
     #[case(b"hello world!", "fc3ff98e8c6a0d3087d515c0473f8677")]
+    // RFC 1321 test suite
+    #[case(b"", "d41d8cd98f00b204e9800998ecf8427e")]
+    #[case(b"a", "0cc175b9c0f1b6a831c399e269772661")]
+    #[case(b"abc", "900150983cd24fb0d6963f7d28e17f72")]
+    #[case(b"message digest", "f96b697d7cb7938d525a2f31aaf161d0")]
+    #[case(b"abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b")]
+    #[case(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+        "d174ab98d277d9f5a5611c2c9f419d9f"
+    )]
+    #[case(
+        b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "57edf4a22be3c955ac49da2e2107b67a"
+    )]
     fn test_hash(#[case] input: &[u8], #[case] expected_hex: &str) {
-        let result_hex: String = hash(input).iter().map(|b| format!("{b:02x}")).collect();
-        assert_eq!(result_hex, expected_hex)
+        assert_eq!(hash(input).to_string(), expected_hex)
+    }
+
+    #[rstest]
+    #[case(1)]
+    #[case(7)]
+    #[case(63)]
+    #[case(64)]
+    #[case(65)]
+    fn test_update_in_pieces(#[case] piece: usize) {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for len in [0, 55, 56, 63, 64, 119, 120, 1000] {
+            let mut md5 = Md5::new();
+            input[..len]
+                .chunks(piece)
+                .for_each(|chunk| md5.update(chunk));
+            assert_eq!(md5.finalize(), hash(&input[..len]), "len {len}");
+        }
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(7)]
+    #[case(609043)]
+    #[case(u64::MAX)]
+    fn test_digits(#[case] n: u64) {
+        let mut buf = [0u8; 20];
+        assert_eq!(digits(n, &mut buf), n.to_string().as_bytes());
+    }
+
+    #[test]
+    fn test_hash_batch_matches_hash() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        // Lengths around every padding boundary, mixed within one batch
+        let lens = [0, 1, 55, 56, 57, 63, 64, 65, 119, 120, 128, 300];
+        for window in lens.windows(8) {
+            let inputs: [&[u8]; 8] = std::array::from_fn(|l| &input[..window[l]]);
+            let expected = inputs.map(hash);
+            assert_eq!(hash_batch(&inputs), expected);
+        }
+        assert_eq!(hash_batch(&["abc"]), [hash("abc")]);
+    }
+
+    #[test]
+    fn test_finalize_batch_after_prefix() {
+        let prefix: Vec<u8> = (0..100).collect();
+        let mut md5 = Md5::new();
+        md5.update(&prefix);
+
+        let suffixes = ["", "1", "609043", &"x".repeat(80)];
+        let expected = suffixes.map(|suffix| hash([&prefix[..], suffix.as_bytes()].concat()));
+        assert_eq!(md5.finalize_batch(&suffixes), expected);
+    }
+
+    #[test]
+    fn test_digest_formatting() {
+        let digest = hash("abc");
+        let expected = "900150983cd24fb0d6963f7d28e17f72";
+        assert_eq!(format!("{digest:x}"), expected);
+        assert_eq!(&digest.to_hex(), expected.as_bytes());
+        assert_eq!(format!("{digest:?}"), format!("Digest({expected})"));
+        assert_eq!(format!("{digest:>34}"), format!("  {expected}"));
+        assert_eq!(format!("{digest:*<34x}"), format!("{expected}**"));
+        assert_eq!(format!("{digest:.4}"), "9001");
+    }
+
+    #[rstest]
+    #[case("abc", 0, 9)]
+    #[case("abc", 1, 0)]
+    #[case("abc", 31, 2)]
+    fn test_nibble(#[case] input: &str, #[case] i: usize, #[case] expected: u8) {
+        assert_eq!(hash(input).nibble(i), expected)
+    }
+
+    #[rstest]
+    #[case("abcdef609043", 5)]
+    #[case("pqrstuv1048970", 5)]
+    #[case("abc", 0)]
+    fn test_leading_zero_nibbles(#[case] input: &str, #[case] expected: u32) {
+        assert_eq!(hash(input).leading_zero_nibbles(), expected)
+    }
+
+    #[rstest]
+    #[case(0, "577571be4de9dcce85a041ba0410f29f")]
+    #[case(1, "eec80a0c92dc8a0777c619d9bb51e910")]
+    #[case(2, "16062ce768787384c81fe17a7a60c7e3")]
+    #[case(2016, "a107ff634856bb300138cac6568c0f24")]
+    fn test_stretch(#[case] rounds: usize, #[case] expected: &str) {
+        assert_eq!(stretch("abc0", rounds).to_string(), expected);
+
+        let inputs = ["abc0", "abc1", "abc2", "abc0"];
+        let batch = stretch_batch(&inputs, rounds);
+        assert_eq!(batch[0].to_string(), expected);
+        assert_eq!(batch[2], stretch("abc2", rounds));
+        assert_eq!(batch[3], batch[0]);
+    }
+
+    #[test]
+    fn test_prefix_reuse() {
+        let mut prefix = Md5::new();
+        prefix.update("abcdef");
+        for suffix in ["609043", "1"] {
+            let mut md5 = prefix.clone();
+            md5.update(suffix);
+            assert_eq!(md5.finalize(), hash(format!("abcdef{suffix}")));
+        }
     }
 }
@@ -1,2 +1,21 @@
+pub mod circuit;
+pub mod combinatorics;
+pub mod cycle;
+pub mod dp;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod intcode;
+pub mod intern;
+pub mod interval;
+pub mod json;
+pub mod linalg;
+pub mod math;
 pub mod md5;
 pub mod parse;
+pub mod rational;
+#[cfg(test)]
+pub(crate) mod rng;
+pub mod search;
+pub mod sweep;
+pub mod vm;
@@ -1,16 +1,85 @@
 pub trait Parse: Sized + Default {
+    /// Whether a leading `-` is read as a sign. Unsigned types skip it.
+    const SIGNED: bool;
+
     fn parse_next(cursor: &mut &[u8]) -> Option<Self>;
 }
 
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+const POW10: [u64; 9] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+];
+
+/// Next 8 bytes as a little-endian word, so byte 0 ends up in the low bits.
+#[inline(always)]
+fn load(cursor: &[u8]) -> Option<u64> {
+    cursor
+        .first_chunk::<8>()
+        .map(|chunk| u64::from_le_bytes(*chunk))
+}
+
+/// High bit set in every byte of `word` that is an ASCII digit.
+#[inline(always)]
+fn digit_mask(word: u64) -> u64 {
+    // Digits become 0..=9; `| HI` keeps the subtraction from borrowing across bytes
+    let t = word ^ (b'0' as u64 * LO);
+    !(((t | HI) - 10 * LO) | t) & HI
+}
+
+/// Number of leading (lowest) bytes of `word` that are digits, `0..=8`.
+#[inline(always)]
+fn digit_run(word: u64) -> usize {
+    ((!digit_mask(word) & HI).trailing_zeros() / 8) as usize
+}
+
+/// Value of the first `len` (`0..=8`) digits of `word`, three multiplies instead of eight.
+#[inline(always)]
+fn eight_digits(word: u64, len: usize) -> u64 {
+    // Shift the digits to the top, the vacated low bytes read as leading zeros
+    let val = word.checked_shl(64 - 8 * len as u32).unwrap_or(0);
+    let val = ((val & 0x0F0F_0F0F_0F0F_0F0F).wrapping_mul(10 << 8 | 1)) >> 8;
+    let val = ((val & 0x00FF_00FF_00FF_00FF).wrapping_mul(100 << 16 | 1)) >> 16;
+    ((val & 0x0000_FFFF_0000_FFFF).wrapping_mul(10000 << 32 | 1)) >> 32
+}
+
+// Accumulates up to 8 digits per step while a full word is left, returning at
+// the first short run. Near the end of the input the byte loop that follows
+// takes over, so results (and overflow panics) match it exactly.
+macro_rules! swar_digits {
+    (scalar, $acc:ident, $cursor:ident, $t:ty, $op:tt) => {};
+    (swar, $acc:ident, $cursor:ident, $t:ty, $op:tt) => {
+        while let Some(word) = load($cursor) {
+            let len = digit_run(word);
+            $acc = $acc * POW10[len] as $t $op eight_digits(word, len) as $t;
+            *$cursor = &$cursor[len..];
+            if len < 8 {
+                return Some($acc);
+            }
+        }
+    };
+}
+
 macro_rules! impl_unsigned {
-    ($($t:ty),*) => {
+    ($mode:ident: $($t:ty),*) => {
         $(
             impl Parse for $t {
+                const SIGNED: bool = false;
+
                 #[inline(always)]
                 fn parse_next(cursor: &mut &[u8]) -> Option<Self> {
                     while !cursor.is_empty() {
                         if cursor[0].is_ascii_digit() {
                             let mut acc: $t = 0;
+                            swar_digits!($mode, acc, cursor, $t, +);
                             while !cursor.is_empty() && cursor[0].is_ascii_digit() {
                                 acc = acc * 10 + (cursor[0] - b'0') as $t;
                                 *cursor = &cursor[1..];
@@ -27,14 +96,17 @@ macro_rules! impl_unsigned {
 }
 
 macro_rules! impl_signed {
-    ($($t:ty),*) => {
+    ($mode:ident: $($t:ty),*) => {
         $(
             impl Parse for $t {
-               #[inline(always)]
+                const SIGNED: bool = true;
+
+                #[inline(always)]
                 fn parse_next(cursor: &mut &[u8]) -> Option<Self> {
                     while !cursor.is_empty() {
                         if cursor[0].is_ascii_digit() {
                             let mut acc: $t = 0;
+                            swar_digits!($mode, acc, cursor, $t, +);
                             while !cursor.is_empty() && cursor[0].is_ascii_digit() {
                                 acc = acc * 10 + (cursor[0] - b'0') as $t;
                                 *cursor = &cursor[1..];
@@ -44,6 +116,7 @@ macro_rules! impl_signed {
                         else if cursor[0] == b'-' && cursor.len() > 1 && cursor[1].is_ascii_digit() {
                             *cursor = &cursor[1..];
                             let mut acc: $t = 0;
+                            swar_digits!($mode, acc, cursor, $t, -);
                             while !cursor.is_empty() && cursor[0].is_ascii_digit() {
                                 acc = acc * 10 - (cursor[0] - b'0') as $t;
                                 *cursor = &cursor[1..];
@@ -60,8 +133,11 @@ macro_rules! impl_signed {
     };
 }
 
-impl_unsigned!(u8, u16, u32, u64, u128, usize);
-impl_signed!(i8, i16, i32, i64, i128, isize);
+// Narrow types overflow within a couple of digits, so word steps don't pay off
+impl_unsigned!(scalar: u8, u16);
+impl_unsigned!(swar: u32, u64, u128, usize);
+impl_signed!(scalar: i8, i16);
+impl_signed!(swar: i32, i64, i128, isize);
 
 pub fn numbers<T: Parse, S: AsRef<[u8]> + ?Sized>(text: &S) -> impl Iterator<Item = T> + '_ {
     let mut cursor = text.as_ref();
@@ -76,6 +152,150 @@ pub fn array<T: Default + Copy, const N: usize>(iter: &mut impl Iterator<Item =
     arr
 }
 
+/// Splits on `\n`, dropping a trailing `\r` and the empty line after a final newline.
+pub fn lines<S: AsRef<[u8]> + ?Sized>(text: &S) -> impl Iterator<Item = &[u8]> + '_ {
+    let mut cursor = text.as_ref();
+    std::iter::from_fn(move || {
+        if cursor.is_empty() {
+            return None;
+        }
+        let end = cursor.iter().position(|&b| b == b'\n');
+        let (line, rest) = match end {
+            Some(i) => (&cursor[..i], &cursor[i + 1..]),
+            None => (cursor, &cursor[cursor.len()..]),
+        };
+        cursor = rest;
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    })
+}
+
+/// Numbers grouped per line, without collecting each line into a `Vec`.
+pub fn numbers_per_line<'a, T: Parse + 'a, S: AsRef<[u8]> + ?Sized>(
+    text: &'a S,
+) -> impl Iterator<Item = impl Iterator<Item = T> + 'a> + 'a {
+    lines(text).map(numbers)
+}
+
+/// Groups of lines separated by one or more blank lines.
+pub fn blocks<S: AsRef<[u8]> + ?Sized>(text: &S) -> impl Iterator<Item = &[u8]> + '_ {
+    let mut cursor = text.as_ref();
+    std::iter::from_fn(move || {
+        // Skip leading blank lines
+        while let Some(rest) = cursor
+            .strip_prefix(b"\n")
+            .or_else(|| cursor.strip_prefix(b"\r\n"))
+        {
+            cursor = rest;
+        }
+        if cursor.is_empty() {
+            return None;
+        }
+
+        let mut end = 0;
+        for line in lines(cursor) {
+            if line.is_empty() {
+                break;
+            }
+            end = line.as_ptr() as usize - cursor.as_ptr() as usize + line.len();
+        }
+
+        let block = &cursor[..end];
+        cursor = &cursor[end..];
+        Some(block)
+    })
+}
+
+/// The `n`-th number (0-based) of every line that has one.
+pub fn column<'a, T: Parse + 'a, S: AsRef<[u8]> + ?Sized>(
+    text: &'a S,
+    n: usize,
+) -> impl Iterator<Item = T> + 'a {
+    numbers_per_line(text).filter_map(move |mut line| line.nth(n))
+}
+
+/// Transposes the first `N` numbers of every line into `N` columns.
+pub fn columns<T: Parse, S: AsRef<[u8]> + ?Sized, const N: usize>(text: &S) -> [Vec<T>; N] {
+    let mut cols = std::array::from_fn(|_| Vec::new());
+    for mut line in numbers_per_line::<T, _>(text) {
+        for col in cols.iter_mut() {
+            col.push(line.next().expect("Not enough numbers on line"));
+        }
+    }
+    cols
+}
+
+/// Splits `line` at the first `sep`, trimming ASCII whitespace around both halves.
+/// An empty `sep` never matches.
+pub fn key_value<'a>(line: &'a [u8], sep: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    if sep.is_empty() {
+        return None;
+    }
+    let i = line.windows(sep.len()).position(|w| w == sep)?;
+    Some((line[..i].trim_ascii(), line[i + sep.len()..].trim_ascii()))
+}
+
+/// [`key_value`] applied to every line, skipping lines without `sep`.
+pub fn key_values<'a, S: AsRef<[u8]> + ?Sized>(
+    text: &'a S,
+    sep: &'a [u8],
+) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + 'a {
+    lines(text).filter_map(move |line| key_value(line, sep))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// A letter followed by letters, digits or `_`.
+    Word(&'a str),
+    /// Digits with an optional leading `-`, as [`numbers`] would read them.
+    Number(&'a str),
+}
+
+impl Token<'_> {
+    /// The number as a `T`. `None` for a word, or a negative number and an
+    /// unsigned `T`.
+    pub fn number<T: Parse>(&self) -> Option<T> {
+        match self {
+            Token::Number(s) if T::SIGNED || !s.starts_with('-') => {
+                T::parse_next(&mut s.as_bytes())
+            }
+            Token::Number(_) => None,
+            Token::Word(_) => None,
+        }
+    }
+}
+
+/// Words and numbers of `text` as borrowed slices, skipping everything else.
+pub fn tokens(text: &str) -> impl Iterator<Item = Token<'_>> + '_ {
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        while pos < bytes.len() {
+            let start = pos;
+            let ch = bytes[pos];
+            let is_number = ch.is_ascii_digit()
+                || (ch == b'-' && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit));
+
+            if is_number {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                return Some(Token::Number(&text[start..pos]));
+            }
+            if ch.is_ascii_alphabetic() {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                return Some(Token::Word(&text[start..pos]));
+            }
+            pos += 1;
+        }
+        None
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +356,67 @@ mod tests {
         let _result: Vec<i8> = numbers(text).collect();
     }
 
+    /// The byte-at-a-time parser the word-at-a-time path has to agree with.
+    fn scalar_i64(text: &str) -> Vec<i64> {
+        let bytes = text.as_bytes();
+        let mut result = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let neg = bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+            if neg || bytes[i].is_ascii_digit() {
+                i += neg as usize;
+                let sign = if neg { -1 } else { 1 };
+                let mut acc: i64 = 0;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    acc = acc * 10 + sign * (bytes[i] - b'0') as i64;
+                    i += 1;
+                }
+                result.push(acc);
+            } else {
+                i += 1;
+            }
+        }
+        result
+    }
+
+    #[rstest]
+    #[case("1234567890123456789")]
+    #[case("-9223372036854775808 9223372036854775807")]
+    #[case("a-b--1 x-12345678-90 0000000001 12345678")]
+    #[case("just some long text without any digits at all, -, --, -")]
+    #[case("1,22,333,4444,55555,666666,7777777,88888888,999999999")]
+    fn test_i64_matches_scalar(#[case] input: &str) {
+        let result: Vec<i64> = numbers(input).collect();
+        assert_eq!(result, scalar_i64(input));
+    }
+
+    #[test]
+    fn test_every_alignment() {
+        let digits = "98765432109876543";
+        for pad in 0..16 {
+            for len in 1..digits.len() {
+                let text = format!("{}{} x", "ab-".repeat(pad), &digits[..len]);
+                let result: Vec<u64> = numbers(&text).collect();
+                assert_eq!(result, vec![digits[..len].parse().unwrap()], "{text}");
+            }
+        }
+    }
+
+    #[rstest]
+    #[case("4294967295 and some padding", vec![u32::MAX as u128])]
+    #[case("340282366920938463463374607431768211455", vec![u128::MAX])]
+    fn test_wide_unsigned(#[case] input: &str, #[case] expected: Vec<u128>) {
+        let result: Vec<u128> = numbers::<u128, _>(input).collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow")]
+    fn test_u32_overflow_panics() {
+        let text = "4294967296 and some padding";
+        let _result: Vec<u32> = numbers(text).collect();
+    }
+
     #[test]
     fn test_array() {
         let text = "10, 20, 30, 40";
@@ -143,4 +424,90 @@ mod tests {
         let arr: [u8; 4] = array(&mut iter);
         assert_eq!(arr, [10, 20, 30, 40]);
     }
+
+    #[rstest]
+    #[case("a\nb\n", vec!["a", "b"])]
+    #[case("a\r\nb", vec!["a", "b"])]
+    #[case::inner_blank("a\n\nb", vec!["a", "", "b"])]
+    #[case::empty("", vec![])]
+    fn test_lines(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let result: Vec<&[u8]> = lines(input).collect();
+        let expected: Vec<&[u8]> = expected.iter().map(|s| s.as_bytes()).collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_numbers_per_line() {
+        let text = "1x2x3\n\n4 -5\n";
+        let result: Vec<Vec<i32>> = numbers_per_line(text).map(Iterator::collect).collect();
+        assert_eq!(result, vec![vec![1, 2, 3], vec![], vec![4, -5]]);
+    }
+
+    #[rstest]
+    #[case("1\n2\n\n3\n", vec!["1\n2", "3"])]
+    #[case::many_blanks("\n\n1\n\n\n\n2", vec!["1", "2"])]
+    #[case::crlf("1\r\n\r\n2\r\n", vec!["1", "2"])]
+    fn test_blocks(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let result: Vec<&[u8]> = blocks(input).collect();
+        let expected: Vec<&[u8]> = expected.iter().map(|s| s.as_bytes()).collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_column() {
+        let text = "3   4\n4   3\n2\n";
+        let result: Vec<u32> = column(text, 1).collect();
+        assert_eq!(result, vec![4, 3]);
+    }
+
+    #[test]
+    fn test_columns() {
+        let text = "3   4\n4   3\n2   5\n";
+        let [left, right] = columns::<u32, _, 2>(text);
+        assert_eq!(left, vec![3, 4, 2]);
+        assert_eq!(right, vec![4, 3, 5]);
+    }
+
+    #[rstest]
+    #[case("123 -> x", Some(("123", "x")))]
+    #[case("a: b: c", Some(("a", "b: c")))]
+    #[case("no separator", None)]
+    fn test_key_value(#[case] input: &str, #[case] expected: Option<(&str, &str)>) {
+        let sep: &[u8] = if input.contains("->") { b"->" } else { b":" };
+        let expected = expected.map(|(k, v)| (k.as_bytes(), v.as_bytes()));
+        assert_eq!(key_value(input.as_bytes(), sep), expected);
+        assert_eq!(key_value(input.as_bytes(), b""), None);
+    }
+
+    #[test]
+    fn test_key_values() {
+        let text = "x AND y -> d\nNOT x -> h\n";
+        let result: Vec<(&[u8], &[u8])> = key_values(text, b"->").collect();
+        assert_eq!(
+            result,
+            vec![(&b"x AND y"[..], &b"d"[..]), (&b"NOT x"[..], &b"h"[..])]
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        use Token::*;
+
+        let result: Vec<Token> = tokens("123 -> x\nNOT lf_2 -> -5-6").collect();
+        assert_eq!(
+            result,
+            vec![
+                Number("123"),
+                Word("x"),
+                Word("NOT"),
+                Word("lf_2"),
+                Number("-5"),
+                Number("-6"),
+            ]
+        );
+        assert_eq!(result[4].number::<i8>(), Some(-5));
+        assert_eq!(result[1].number::<u8>(), None);
+        assert_eq!(result[4].number::<u32>(), None);
+        assert_eq!(result[0].number::<u32>(), Some(123));
+    }
 }
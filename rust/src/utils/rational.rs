@@ -0,0 +1,282 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::math::gcd;
+
+/// An exact fraction, always stored in lowest terms with a positive
+/// denominator, so equal values compare and hash equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ratio<T = i128> {
+    num: T,
+    den: T,
+}
+
+macro_rules! impl_ratio {
+    ($($t:ty),*) => {$(
+        impl Ratio<$t> {
+            pub const ZERO: Self = Self { num: 0, den: 1 };
+            pub const ONE: Self = Self { num: 1, den: 1 };
+
+            /// `num / den` in lowest terms. Panics if `den` is zero.
+            pub fn new(num: $t, den: $t) -> Self {
+                assert!(den != 0, "Denominator should not be zero");
+                let g = gcd(num, den) * den.signum();
+                Self { num: num / g, den: den / g }
+            }
+
+            pub fn numer(self) -> $t {
+                self.num
+            }
+
+            pub fn denom(self) -> $t {
+                self.den
+            }
+
+            pub fn is_integer(self) -> bool {
+                self.den == 1
+            }
+
+            pub fn to_integer(self) -> Option<$t> {
+                self.is_integer().then_some(self.num)
+            }
+
+            /// Rounds towards negative infinity.
+            pub fn floor(self) -> $t {
+                self.num.div_euclid(self.den)
+            }
+
+            pub fn abs(self) -> Self {
+                Self { num: self.num.abs(), den: self.den }
+            }
+
+            /// `1 / self`. Panics on zero.
+            pub fn recip(self) -> Self {
+                Self::new(self.den, self.num)
+            }
+        }
+
+        impl Default for Ratio<$t> {
+            fn default() -> Self {
+                Self::ZERO
+            }
+        }
+
+        impl From<$t> for Ratio<$t> {
+            fn from(n: $t) -> Self {
+                Self { num: n, den: 1 }
+            }
+        }
+
+        impl Ord for Ratio<$t> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                // Denominators are positive, so cross-multiplying keeps the order
+                if let (Some(lhs), Some(rhs)) =
+                    (self.num.checked_mul(other.den), other.num.checked_mul(self.den))
+                {
+                    return lhs.cmp(&rhs);
+                }
+
+                // Otherwise walk the continued fractions, which never overflows:
+                // equal integer parts leave the fractional parts to compare
+                let (mut a, mut b) = ((self.num, self.den), (other.num, other.den));
+                loop {
+                    let (qa, qb) = (a.0.div_euclid(a.1), b.0.div_euclid(b.1));
+                    if qa != qb {
+                        return qa.cmp(&qb);
+                    }
+                    match (a.0.rem_euclid(a.1), b.0.rem_euclid(b.1)) {
+                        (0, 0) => return Ordering::Equal,
+                        (0, _) => return Ordering::Less,
+                        (_, 0) => return Ordering::Greater,
+                        // ra / da < rb / db exactly when db / rb < da / ra
+                        (ra, rb) => (a, b) = ((b.1, rb), (a.1, ra)),
+                    }
+                }
+            }
+        }
+
+        impl PartialOrd for Ratio<$t> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Neg for Ratio<$t> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { num: -self.num, den: self.den }
+            }
+        }
+
+        // Operands are reduced against each other first to put off overflow
+        impl Add for Ratio<$t> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                let g = gcd(self.den, rhs.den);
+                let num = self.num * (rhs.den / g) + rhs.num * (self.den / g);
+                Self::new(num, self.den / g * rhs.den)
+            }
+        }
+
+        impl Sub for Ratio<$t> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                self + -rhs
+            }
+        }
+
+        impl Mul for Ratio<$t> {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                let (g1, g2) = (gcd(self.num, rhs.den).max(1), gcd(rhs.num, self.den).max(1));
+                Self::new(
+                    (self.num / g1) * (rhs.num / g2),
+                    (self.den / g2) * (rhs.den / g1),
+                )
+            }
+        }
+
+        impl Div for Ratio<$t> {
+            type Output = Self;
+
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, rhs: Self) -> Self {
+                self * rhs.recip()
+            }
+        }
+
+        impl fmt::Display for Ratio<$t> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if self.den == 1 {
+                    write!(f, "{}", self.num)
+                } else {
+                    write!(f, "{}/{}", self.num, self.den)
+                }
+            }
+        }
+    )*};
+}
+
+impl_ratio!(i32, i64, i128);
+
+macro_rules! impl_assign_ops {
+    ($($trait:ident $method:ident $op:ident $op_method:ident),*) => {$(
+        impl<T> $trait for Ratio<T>
+        where
+            Ratio<T>: Copy + $op<Output = Ratio<T>>,
+        {
+            fn $method(&mut self, rhs: Self) {
+                *self = self.$op_method(rhs);
+            }
+        }
+    )*};
+}
+
+impl_assign_ops!(
+    AddAssign add_assign Add add,
+    SubAssign sub_assign Sub sub,
+    MulAssign mul_assign Mul mul,
+    DivAssign div_assign Div div
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use rstest::rstest;
+
+    type Q = Ratio<i128>;
+    type R64 = Ratio<i64>;
+
+    fn r(num: i128, den: i128) -> Q {
+        Q::new(num, den)
+    }
+
+    #[rstest]
+    #[case(2, -4, -1, 2)]
+    #[case(-6, -9, 2, 3)]
+    #[case(0, -5, 0, 1)]
+    #[case(7, 1, 7, 1)]
+    fn test_normalize(#[case] num: i128, #[case] den: i128, #[case] n: i128, #[case] d: i128) {
+        let ratio = r(num, den);
+        assert_eq!((ratio.numer(), ratio.denom()), (n, d));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(3, 4), r(-1, 4));
+        assert_eq!(r(2, 3) * r(9, 4), r(3, 2));
+        assert_eq!(r(2, 3) / r(-4, 9), r(-3, 2));
+        let mut x = Q::from(5);
+        x -= r(1, 2);
+        x *= r(2, 3);
+        x += Q::ONE;
+        x /= r(1, 4);
+        assert_eq!(x, Q::from(16));
+        assert_eq!((x.to_integer(), r(7, 2).to_integer()), (Some(16), None));
+        assert_eq!((r(7, 2).floor(), r(-7, 2).floor()), (3, -4));
+        assert_eq!(format!("{} {}", r(-7, 2), r(4, 2)), "-7/2 2");
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        // Every ratio with small terms, against cross-multiplied integers
+        let values: Vec<(i64, i64)> = (-6..=6)
+            .flat_map(|n| (1..=6).map(move |d| (n, d)))
+            .collect();
+        for &(a, b) in &values {
+            for &(c, d) in &values {
+                let (x, y) = (R64::new(a, b), R64::new(c, d));
+                assert_eq!(x + y, R64::new(a * d + c * b, b * d));
+                assert_eq!(x * y, R64::new(a * c, b * d));
+                assert_eq!(x.cmp(&y), (a * d).cmp(&(c * b)));
+                assert_eq!(x == y, a * d == c * b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_early_overflow() {
+        // The cross products would overflow, the reduced ones don't
+        let big = i128::MAX / 3;
+        assert_eq!(r(big, 7) * r(7, big), Q::ONE);
+        assert_eq!(r(1, big) + r(1, big), r(2, big));
+    }
+
+    #[test]
+    fn test_cmp_large_terms() {
+        // Cross products overflow i64, but not the i128 they are checked against
+        let mut rng = Rng::default();
+        let mut term = || (rng.next_u64() >> 1) as i64;
+        for _ in 0..1000 {
+            // Terms of similar size often share the integer part, so the
+            // fractional parts decide
+            let (a, b) = (term() - term(), term().max(1));
+            let (c, d) = (term() - term(), term().max(1));
+            let (x, y) = (R64::new(a, b), R64::new(c, d));
+            let (x2, y2) = (
+                x.numer() as i128 * y.denom() as i128,
+                y.numer() as i128 * x.denom() as i128,
+            );
+            assert_eq!(x.cmp(&y), x2.cmp(&y2), "{x} vs {y}");
+        }
+
+        let max = i128::MAX;
+        assert!(r(max, max - 1) < r(max - 1, max - 2));
+        assert!(r(-max, max - 1) > r(-(max - 1), max - 2));
+        assert!(r(i128::MIN + 1, 3) < r(max, -5));
+        assert_eq!(r(max, 2).cmp(&r(max, 2)), Ordering::Equal);
+    }
+
+    #[test]
+    #[should_panic(expected = "Denominator should not be zero")]
+    fn test_zero_denominator() {
+        Q::ZERO.recip();
+    }
+}
@@ -0,0 +1,30 @@
+//! Xorshift64, a tiny deterministic generator for tests and benches. Not for
+//! anything that needs good randomness.
+
+pub struct Rng(u64);
+
+impl Rng {
+    /// The seed must not be zero, which xorshift never leaves.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "Seed should not be zero");
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A value in `0..n`, with a slight bias that doesn't matter here.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0x2545_f491_4f6c_dd1d)
+    }
+}
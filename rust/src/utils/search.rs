@@ -0,0 +1,165 @@
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+// Indices per unit of work for `parallel_first`
+const CHUNK: u64 = 4096;
+
+/// First and last index of `range`, or `None` if it is empty.
+fn bounds(range: impl RangeBounds<u64>) -> Option<(u64, u64)> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let last = match range.end_bound() {
+        Bound::Included(&end) => end,
+        Bound::Excluded(&end) => end.checked_sub(1)?,
+        Bound::Unbounded => u64::MAX,
+    };
+    (start <= last).then_some((start, last))
+}
+
+/// `range` cut into consecutive chunks of `size` indices, the last one possibly
+/// shorter.
+fn chunks(range: impl RangeBounds<u64>, size: u64) -> impl Iterator<Item = RangeInclusive<u64>> {
+    assert!(size > 0, "Chunks should not be empty");
+    let (start, last) = bounds(range).unwrap_or((1, 0));
+    let chunk = move |start: u64| start..=start.saturating_add(size - 1).min(last);
+    std::iter::successors((start <= last).then(|| chunk(start)), move |prev| {
+        prev.end()
+            .checked_add(1)
+            .filter(|&next| next <= last)
+            .map(chunk)
+    })
+}
+
+/// Smallest index in `range` satisfying `predicate`, searched on all cores.
+pub fn parallel_first<F>(range: impl RangeBounds<u64>, predicate: F) -> Option<u64>
+where
+    F: Fn(u64) -> bool + Sync,
+{
+    parallel_first_chunked(range, CHUNK, |chunk| {
+        chunk.into_iter().find(|&i| predicate(i))
+    })
+}
+
+/// Like [`parallel_first`], but `find` gets a whole chunk of up to `chunk_size`
+/// indices and returns its first match, so it can batch work within a chunk.
+///
+/// Chunks are handed out in increasing order and nothing past a found match is
+/// started, so the result is the same as a sequential scan. Without the
+/// `parallel` feature, it is one.
+pub fn parallel_first_chunked<F>(
+    range: impl RangeBounds<u64>,
+    chunk_size: u64,
+    find: F,
+) -> Option<u64>
+where
+    F: Fn(RangeInclusive<u64>) -> Option<u64> + Sync,
+{
+    scan(chunks(range, chunk_size), find)
+}
+
+#[cfg(feature = "parallel")]
+fn scan<F>(chunks: impl Iterator<Item = RangeInclusive<u64>> + Send, find: F) -> Option<u64>
+where
+    F: Fn(RangeInclusive<u64>) -> Option<u64> + Sync,
+{
+    use std::sync::Mutex;
+
+    let chunks = Mutex::new(chunks);
+    let best = Mutex::new(None::<u64>);
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let Some(chunk) = chunks.lock().unwrap().next() else {
+                        break;
+                    };
+                    // Later chunks only start further past the match
+                    if best.lock().unwrap().is_some_and(|b| b <= *chunk.start()) {
+                        break;
+                    }
+                    if let Some(i) = find(chunk) {
+                        let mut best = best.lock().unwrap();
+                        *best = Some(best.map_or(i, |b| b.min(i)));
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    best.into_inner().unwrap()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scan<F>(mut chunks: impl Iterator<Item = RangeInclusive<u64>>, find: F) -> Option<u64>
+where
+    F: Fn(RangeInclusive<u64>) -> Option<u64>,
+{
+    chunks.find_map(find)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0..100_000, Some(9_999))]
+    #[case(10_000..100_000, Some(19_999))]
+    #[case(..=9_998, None)]
+    #[case(0..0, None)]
+    fn test_parallel_first(#[case] range: impl RangeBounds<u64>, #[case] expected: Option<u64>) {
+        assert_eq!(parallel_first(range, |i| i % 10_000 == 9_999), expected);
+    }
+
+    #[test]
+    fn test_deterministic_with_many_matches() {
+        // Every chunk has matches, only the first may win
+        for _ in 0..20 {
+            assert_eq!(
+                parallel_first_chunked(3.., 7, |c| c.into_iter().find(|i| i % 5 == 0)),
+                Some(5)
+            );
+        }
+    }
+
+    #[rstest]
+    #[case((Bound::Excluded(u64::MAX), Bound::Unbounded), None)]
+    #[case((Bound::Unbounded, Bound::Excluded(0)), None)]
+    #[case((Bound::Included(u64::MAX - 5), Bound::Unbounded), Some(u64::MAX))]
+    #[case((Bound::Included(u64::MAX), Bound::Included(u64::MAX)), Some(u64::MAX))]
+    fn test_top_of_range(#[case] range: (Bound<u64>, Bound<u64>), #[case] expected: Option<u64>) {
+        assert_eq!(parallel_first(range, |i| i == u64::MAX), expected);
+        assert_eq!(
+            parallel_first_chunked(range, 4, |c| c.into_iter().find(|&i| i == u64::MAX)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_lowest_of_many_matches() {
+        // Matches in every chunk after the first few, whichever worker finds one first
+        for _ in 0..20 {
+            assert_eq!(
+                parallel_first(0.., |i| i >= 3 * CHUNK + 5),
+                Some(3 * CHUNK + 5)
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(0..10, 3, vec![0..=2, 3..=5, 6..=8, 9..=9])]
+    #[case(u64::MAX - 3..=u64::MAX, 2, vec![u64::MAX - 3..=u64::MAX - 2, u64::MAX - 1..=u64::MAX])]
+    #[case(5..5, 2, vec![])]
+    fn test_chunks(
+        #[case] range: impl RangeBounds<u64>,
+        #[case] size: u64,
+        #[case] expected: Vec<RangeInclusive<u64>>,
+    ) {
+        assert_eq!(chunks(range, size).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,249 @@
+use std::ops::{Bound, Range, RangeBounds};
+
+/// An update applied to every cell of a rectangle. Cells start at 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Sets the cell to 1.
+    Set,
+    /// Sets the cell to 0.
+    Clear,
+    /// 0 becomes 1, anything else becomes 0.
+    Toggle,
+    Add(i64),
+    /// Subtracts, stopping at 0.
+    SaturatingSub(i64),
+}
+
+fn to_range(range: impl RangeBounds<i64>) -> Range<i64> {
+    let past = |n: i64| {
+        n.checked_add(1)
+            .expect("Rectangles should end below i64::MAX")
+    };
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => past(start),
+        Bound::Unbounded => panic!("Rectangles should be bounded"),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => past(end),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => panic!("Rectangles should be bounded"),
+    };
+    start..end
+}
+
+/// Sorted, distinct ends of `ranges`.
+fn edges<'a>(ranges: impl Iterator<Item = &'a Range<i64>>) -> Vec<i64> {
+    let mut edges: Vec<i64> = ranges.flat_map(|r| [r.start, r.end]).collect();
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+/// Applies and drains a run of additions on `width`-wide blocks. A long run goes
+/// through a 2D difference array, which costs one pass over every block, a short
+/// one just updates the blocks each rectangle covers.
+fn add_all(values: &mut [i64], width: usize, adds: &mut Vec<(Range<usize>, Range<usize>, i64)>) {
+    let covered: usize = adds.iter().map(|(bx, by, _)| bx.len() * by.len()).sum();
+    if covered <= values.len() {
+        for (bx, by, n) in adds.drain(..) {
+            for j in by {
+                for value in &mut values[j * width + bx.start..j * width + bx.end] {
+                    *value += n;
+                }
+            }
+        }
+        return;
+    }
+
+    // One extra row and column for the closing corners
+    let stride = width + 1;
+    let mut diff = vec![0i64; (values.len() / width + 1) * stride];
+    for (bx, by, n) in adds.drain(..) {
+        diff[by.start * stride + bx.start] += n;
+        diff[by.start * stride + bx.end] -= n;
+        diff[by.end * stride + bx.start] -= n;
+        diff[by.end * stride + bx.end] += n;
+    }
+    for row in diff.chunks_exact_mut(stride) {
+        for i in 1..stride {
+            row[i] += row[i - 1];
+        }
+    }
+    for k in stride..diff.len() {
+        diff[k] += diff[k - stride];
+    }
+    for (row, sums) in values
+        .chunks_exact_mut(width)
+        .zip(diff.chunks_exact(stride))
+    {
+        for (value, sum) in row.iter_mut().zip(sums) {
+            *value += sum;
+        }
+    }
+}
+
+/// A batch of rectangle updates on an unbounded integer plane, answering
+/// queries over the result without touching every cell.
+///
+/// Only the rectangle edges matter, so the plane is compressed into blocks
+/// between consecutive edges. Long runs of [`Op::Add`] go through a 2D
+/// difference array, other operations update the blocks they cover.
+#[derive(Clone, Debug, Default)]
+pub struct Sweep {
+    ops: Vec<(Op, Range<i64>, Range<i64>)>,
+}
+
+impl Sweep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `op` on columns `xs` of rows `ys`. Later operations apply on top
+    /// of earlier ones. Panics if a range is unbounded or includes `i64::MAX`.
+    pub fn push(&mut self, op: Op, xs: impl RangeBounds<i64>, ys: impl RangeBounds<i64>) {
+        let (xs, ys) = (to_range(xs), to_range(ys));
+        if !xs.is_empty() && !ys.is_empty() {
+            self.ops.push((op, xs, ys));
+        }
+    }
+
+    /// Number of non-zero cells.
+    pub fn count(&self) -> u64 {
+        self.blocks()
+            .into_iter()
+            .filter(|&(_, value)| value != 0)
+            .map(|(area, _)| area)
+            .sum()
+    }
+
+    /// Sum of all cells.
+    pub fn sum(&self) -> i64 {
+        self.blocks()
+            .into_iter()
+            .map(|(area, value)| area as i64 * value)
+            .sum()
+    }
+
+    /// Area and value of every compressed block.
+    fn blocks(&self) -> Vec<(u64, i64)> {
+        let xs = edges(self.ops.iter().map(|(_, xs, _)| xs));
+        let ys = edges(self.ops.iter().map(|(_, _, ys)| ys));
+        let index = |edges: &[i64], range: &Range<i64>| {
+            edges.binary_search(&range.start).unwrap()..edges.binary_search(&range.end).unwrap()
+        };
+
+        // Block (i, j) spans xs[i]..xs[i + 1] and ys[j]..ys[j + 1]
+        let width = xs.len().saturating_sub(1);
+        let height = ys.len().saturating_sub(1);
+        let mut values = vec![0i64; width * height];
+        let mut adds = Vec::new();
+
+        for (op, x, y) in &self.ops {
+            let (bx, by) = (index(&xs, x), index(&ys, y));
+            if let Op::Add(n) = *op {
+                adds.push((bx, by, n));
+                continue;
+            }
+
+            add_all(&mut values, width, &mut adds);
+            for j in by {
+                for value in &mut values[j * width + bx.start..j * width + bx.end] {
+                    *value = match *op {
+                        Op::Set => 1,
+                        Op::Clear => 0,
+                        Op::Toggle => (*value == 0) as i64,
+                        Op::SaturatingSub(n) => (*value - n).max(0),
+                        Op::Add(_) => unreachable!(),
+                    };
+                }
+            }
+        }
+        add_all(&mut values, width, &mut adds);
+
+        let areas = ys.windows(2).flat_map(|y| {
+            xs.windows(2)
+                .map(move |x| ((x[1] - x[0]) * (y[1] - y[0])) as u64)
+        });
+        areas.zip(values).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::rng::Rng;
+
+    /// Random ops on a small plane, applied both ways.
+    fn check_against_grid(seed: u64, ops: &[Op]) {
+        let mut rng = Rng::new(seed);
+        let mut next = |n: u64| rng.below(n) as i64;
+
+        let mut sweep = Sweep::new();
+        let mut grid = Grid::new(20, 20, 0i64);
+        for _ in 0..50 {
+            let op = ops[next(ops.len() as u64) as usize];
+            let (x1, y1) = (next(20), next(20));
+            let (x2, y2) = (x1 + next((20 - x1) as u64), y1 + next((20 - y1) as u64));
+
+            sweep.push(op, x1..=x2, y1..=y2);
+            grid.view_mut(x1 as usize..=x2 as usize, y1 as usize..=y2 as usize)
+                .map_in_place(|&v| match op {
+                    Op::Set => 1,
+                    Op::Clear => 0,
+                    Op::Toggle => (v == 0) as i64,
+                    Op::Add(n) => v + n,
+                    Op::SaturatingSub(n) => (v - n).max(0),
+                });
+        }
+
+        assert_eq!(sweep.sum(), grid.iter().sum::<i64>());
+        assert_eq!(
+            sweep.count(),
+            grid.iter().filter(|&&v| v != 0).count() as u64
+        );
+    }
+
+    #[test]
+    fn test_lights_against_grid() {
+        for seed in 1..20 {
+            check_against_grid(seed, &[Op::Set, Op::Clear, Op::Toggle]);
+        }
+    }
+
+    #[test]
+    fn test_brightness_against_grid() {
+        for seed in 1..20 {
+            check_against_grid(seed, &[Op::Add(1), Op::SaturatingSub(1), Op::Add(2)]);
+        }
+    }
+
+    #[test]
+    fn test_only_adds() {
+        for seed in 1..20 {
+            check_against_grid(seed, &[Op::Add(3), Op::Add(-1)]);
+        }
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        let mut sweep = Sweep::new();
+        sweep.push(Op::Add(2), -1_000_000..1_000_000, 0..1_000_000);
+        sweep.push(Op::Toggle, 0..=0, 0..=0);
+        sweep.push(Op::Add(1), 0..1, 0..1);
+        assert_eq!(sweep.count(), 2_000_000 * 1_000_000);
+        assert_eq!(sweep.sum(), 2 * 2_000_000 * 1_000_000 - 2 + 1);
+        assert_eq!(Sweep::new().sum(), 0);
+
+        let mut sweep = Sweep::new();
+        sweep.push(Op::Set, i64::MAX - 3..i64::MAX, i64::MIN..=i64::MIN + 1);
+        assert_eq!(sweep.count(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rectangles should end below i64::MAX")]
+    fn test_inclusive_max_panics() {
+        Sweep::new().push(Op::Set, 0..=i64::MAX, 0..1);
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+
+use crate::parse::Token;
+
+/// An instruction argument: a register index or an immediate value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Reg(usize),
+    Imm(i64),
+}
+
+impl Operand {
+    pub fn value(self, regs: &[i64]) -> i64 {
+        match self {
+            Operand::Reg(r) => regs[r],
+            Operand::Imm(n) => n,
+        }
+    }
+}
+
+/// Register `a` is 0, `b` is 1 and so on. Fails with the token itself on a
+/// longer word or a number past i64.
+impl<'a> TryFrom<Token<'a>> for Operand {
+    type Error = Token<'a>;
+
+    fn try_from(token: Token<'a>) -> Result<Self, Token<'a>> {
+        let operand = match token {
+            Token::Number(n) => n.parse().ok().map(Operand::Imm),
+            Token::Word(w) => try_register(w).map(Operand::Reg),
+        };
+        operand.ok_or(token)
+    }
+}
+
+/// Index of a single-letter register name, `a` being 0.
+pub fn try_register(name: &str) -> Option<usize> {
+    match name.as_bytes() {
+        &[c @ b'a'..=b'z'] => Some((c - b'a') as usize),
+        _ => None,
+    }
+}
+
+/// [`try_register`], panicking on anything but a single letter.
+pub fn register(name: &str) -> usize {
+    try_register(name).unwrap_or_else(|| panic!("Register names should be a single letter: {name}"))
+}
+
+/// What the machine does after an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    /// Moves the program counter by an offset.
+    Jump(i64),
+    /// Retries the instruction once there is input.
+    Wait,
+    Halt,
+}
+
+/// An opcode set. Instructions see the whole machine, so they can read input,
+/// write output or even rewrite the program.
+pub trait Instruction: Clone + Debug + Sized {
+    fn execute(&self, vm: &mut Vm<Self>) -> Flow;
+}
+
+/// Why the machine stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// A `Halt` or the program counter left the program.
+    Halted,
+    /// About to run the instruction at this address.
+    Breakpoint(usize),
+    /// Blocked on an empty input queue.
+    Waiting,
+}
+
+/// A repeated state: the machine was in the same state after `start` and
+/// `start + length` steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Loop {
+    pub start: u64,
+    pub length: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry<I> {
+    pub pc: usize,
+    pub instruction: I,
+    /// Registers after the instruction ran.
+    pub regs: Vec<i64>,
+}
+
+/// A register machine running a program of `I`.
+#[derive(Clone, Debug)]
+pub struct Vm<I> {
+    pub program: Vec<I>,
+    pub regs: Vec<i64>,
+    pub pc: i64,
+    /// Register mirroring the program counter, as in 2018's `#ip` directive.
+    pub ip_register: Option<usize>,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+    /// Instructions executed so far.
+    pub steps: u64,
+    breakpoints: HashSet<usize>,
+    trace: Option<Vec<TraceEntry<I>>>,
+}
+
+impl<I: Instruction> Vm<I> {
+    pub fn new(program: Vec<I>, registers: usize) -> Self {
+        Self {
+            program,
+            regs: vec![0; registers],
+            pc: 0,
+            ip_register: None,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            steps: 0,
+            breakpoints: HashSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn with_ip_register(mut self, register: usize) -> Self {
+        self.ip_register = Some(register);
+        self
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn remove_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.remove(&pc);
+    }
+
+    /// Starts recording every executed instruction.
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_default();
+    }
+
+    /// The instructions recorded since the last call.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry<I>> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Executes one instruction, unless the machine can't continue.
+    pub fn step(&mut self) -> Result<(), Stop> {
+        let Some(instruction) = usize::try_from(self.pc)
+            .ok()
+            .and_then(|pc| self.program.get(pc))
+            .cloned()
+        else {
+            return Err(Stop::Halted);
+        };
+        if let Some(ip) = self.ip_register {
+            self.regs[ip] = self.pc;
+        }
+
+        let flow = instruction.execute(self);
+        if flow == Flow::Wait {
+            return Err(Stop::Waiting);
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                pc: self.pc as usize,
+                instruction,
+                regs: self.regs.clone(),
+            });
+        }
+        self.steps += 1;
+
+        let base = self.ip_register.map_or(self.pc, |ip| self.regs[ip]);
+        match flow {
+            Flow::Next => self.pc = base + 1,
+            Flow::Jump(offset) => self.pc = base + offset,
+            Flow::Halt => {
+                self.pc = base;
+                return Err(Stop::Halted);
+            }
+            Flow::Wait => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Runs until the machine halts, blocks or reaches a breakpoint. The
+    /// instruction it starts on always runs, so calling this again resumes
+    /// past a breakpoint.
+    pub fn run(&mut self) -> Stop {
+        if let Err(stop) = self.step() {
+            return stop;
+        }
+        loop {
+            if let Ok(pc) = usize::try_from(self.pc)
+                && self.breakpoints.contains(&pc)
+            {
+                return Stop::Breakpoint(pc);
+            }
+            if let Err(stop) = self.step() {
+                return stop;
+            }
+        }
+    }
+
+    /// Runs until the program counter and registers repeat a state seen since
+    /// this call, or the machine stops first.
+    pub fn find_loop(&mut self) -> Result<Loop, Stop> {
+        let mut seen = HashMap::new();
+        loop {
+            if let Some(start) = seen.insert((self.pc, self.regs.clone()), self.steps) {
+                return Ok(Loop {
+                    start,
+                    length: self.steps - start,
+                });
+            }
+            self.step()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::tokens;
+
+    /// 2015 day 23.
+    #[derive(Clone, Debug, PartialEq)]
+    enum Turing {
+        Hlf(usize),
+        Tpl(usize),
+        Inc(usize),
+        Jmp(i64),
+        Jie(usize, i64),
+        Jio(usize, i64),
+    }
+
+    impl Instruction for Turing {
+        fn execute(&self, vm: &mut Vm<Self>) -> Flow {
+            let regs = &mut vm.regs;
+            match *self {
+                Turing::Hlf(r) => regs[r] /= 2,
+                Turing::Tpl(r) => regs[r] *= 3,
+                Turing::Inc(r) => regs[r] += 1,
+                Turing::Jmp(n) => return Flow::Jump(n),
+                Turing::Jie(r, n) if regs[r] % 2 == 0 => return Flow::Jump(n),
+                Turing::Jio(r, n) if regs[r] == 1 => return Flow::Jump(n),
+                Turing::Jie(..) | Turing::Jio(..) => {}
+            }
+            Flow::Next
+        }
+    }
+
+    fn turing(text: &str) -> Vec<Turing> {
+        text.lines()
+            .map(|line| {
+                let (op, rest) = line.trim().split_once(' ').unwrap();
+                let args: Vec<_> = tokens(rest)
+                    .map(Operand::try_from)
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_else(|token| panic!("Bad operand {token:?}: {line}"));
+                match (op, args.as_slice()) {
+                    ("hlf", &[Operand::Reg(r)]) => Turing::Hlf(r),
+                    ("tpl", &[Operand::Reg(r)]) => Turing::Tpl(r),
+                    ("inc", &[Operand::Reg(r)]) => Turing::Inc(r),
+                    ("jmp", &[Operand::Imm(n)]) => Turing::Jmp(n),
+                    ("jie", &[Operand::Reg(r), Operand::Imm(n)]) => Turing::Jie(r, n),
+                    ("jio", &[Operand::Reg(r), Operand::Imm(n)]) => Turing::Jio(r, n),
+                    _ => panic!("Unknown instruction: {line}"),
+                }
+            })
+            .collect()
+    }
+
+    /// 2016's assembunny with a blocking receive.
+    #[derive(Clone, Debug, PartialEq)]
+    enum Bunny {
+        Cpy(Operand, usize),
+        Inc(usize),
+        Dec(usize),
+        Jnz(Operand, Operand),
+        Out(Operand),
+        Rcv(usize),
+    }
+
+    impl Instruction for Bunny {
+        fn execute(&self, vm: &mut Vm<Self>) -> Flow {
+            match *self {
+                Bunny::Cpy(x, r) => vm.regs[r] = x.value(&vm.regs),
+                Bunny::Inc(r) => vm.regs[r] += 1,
+                Bunny::Dec(r) => vm.regs[r] -= 1,
+                Bunny::Jnz(x, n) if x.value(&vm.regs) != 0 => return Flow::Jump(n.value(&vm.regs)),
+                Bunny::Jnz(..) => {}
+                Bunny::Out(x) => vm.output.push(x.value(&vm.regs)),
+                Bunny::Rcv(r) => match vm.input.pop_front() {
+                    Some(n) => vm.regs[r] = n,
+                    None => return Flow::Wait,
+                },
+            }
+            Flow::Next
+        }
+    }
+
+    fn bunny(text: &str) -> Vec<Bunny> {
+        text.lines()
+            .map(|line| {
+                let (op, rest) = line.trim().split_once(' ').unwrap();
+                let args: Vec<_> = tokens(rest)
+                    .map(Operand::try_from)
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_else(|token| panic!("Bad operand {token:?}: {line}"));
+                match (op, args.as_slice()) {
+                    ("cpy", &[x, Operand::Reg(r)]) => Bunny::Cpy(x, r),
+                    ("inc", &[Operand::Reg(r)]) => Bunny::Inc(r),
+                    ("dec", &[Operand::Reg(r)]) => Bunny::Dec(r),
+                    ("jnz", &[x, n]) => Bunny::Jnz(x, n),
+                    ("out", &[x]) => Bunny::Out(x),
+                    ("rcv", &[Operand::Reg(r)]) => Bunny::Rcv(r),
+                    _ => panic!("Unknown instruction: {line}"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_and_breakpoint() {
+        let mut vm = Vm::new(turing("inc a\njio a, +2\ntpl a\ninc a"), 2);
+        vm.add_breakpoint(3);
+        assert_eq!(vm.run(), Stop::Breakpoint(3));
+        assert_eq!((vm.regs[0], vm.steps), (1, 2));
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!((vm.regs[0], vm.steps), (2, 3));
+        assert_eq!(vm.step(), Err(Stop::Halted));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut vm = Vm::new(bunny("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a"), 4);
+        vm.enable_trace();
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.regs[0], 42);
+        let trace = vm.take_trace();
+        let pcs: Vec<_> = trace.iter().map(|entry| entry.pc).collect();
+        assert_eq!(pcs, vec![0, 1, 2, 3, 4]);
+        assert_eq!(trace[1].instruction, Bunny::Inc(0));
+        assert_eq!(trace[1].regs, vec![42, 0, 0, 0]);
+        assert!(vm.take_trace().is_empty());
+    }
+
+    #[test]
+    fn test_io() {
+        let mut vm = Vm::new(bunny("rcv a\nrcv b\nout a\nout b\nout 7"), 2);
+        vm.input.push_back(3);
+        assert_eq!(vm.run(), Stop::Waiting);
+        assert_eq!((vm.pc, vm.steps), (1, 1));
+        vm.input.push_back(4);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.output, vec![3, 4, 7]);
+    }
+
+    #[test]
+    fn test_find_loop() {
+        let mut vm = Vm::new(bunny("cpy 1 a\ndec a\ninc a\njnz 1 -2"), 1);
+        assert_eq!(
+            vm.find_loop(),
+            Ok(Loop {
+                start: 1,
+                length: 3
+            })
+        );
+        let mut vm = Vm::new(bunny("inc a\ninc a"), 1);
+        assert_eq!(vm.find_loop(), Err(Stop::Halted));
+    }
+
+    /// 2018 day 19, where the program counter lives in a register.
+    #[derive(Clone, Debug)]
+    struct Elf([u8; 4]);
+
+    impl Instruction for Elf {
+        fn execute(&self, vm: &mut Vm<Self>) -> Flow {
+            let [op, a, b, c] = self.0.map(|n| n as usize);
+            let regs = &mut vm.regs;
+            regs[c] = match op {
+                0 => regs[a] + regs[b],
+                1 => regs[a] + b as i64,
+                2 => regs[a],
+                _ => a as i64,
+            };
+            Flow::Next
+        }
+    }
+
+    #[test]
+    fn test_ip_register() {
+        // seti 5 0 1, seti 6 0 2, addi 0 1 0, addr 1 2 3, setr 1 0 0, seti 8 0 4, seti 9 0 5
+        let program = [
+            [3, 5, 0, 1],
+            [3, 6, 0, 2],
+            [1, 0, 1, 0],
+            [0, 1, 2, 3],
+            [2, 1, 0, 0],
+            [3, 8, 0, 4],
+            [3, 9, 0, 5],
+        ];
+        let mut vm = Vm::new(program.map(Elf).to_vec(), 6).with_ip_register(0);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.regs, vec![6, 5, 6, 0, 0, 9]);
+        assert_eq!(vm.steps, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Register names should be a single letter: ab")]
+    fn test_bad_register() {
+        register("ab");
+    }
+
+    #[test]
+    fn test_operand_from_token() {
+        use Token::*;
+        assert_eq!(Operand::try_from(Word("c")), Ok(Operand::Reg(2)));
+        assert_eq!(Operand::try_from(Number("-7")), Ok(Operand::Imm(-7)));
+        assert_eq!(Operand::try_from(Word("ab")), Err(Word("ab")));
+        let huge = Number("99999999999999999999");
+        assert_eq!(Operand::try_from(huge), Err(huge));
+    }
+}